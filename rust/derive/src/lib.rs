@@ -0,0 +1,100 @@
+// =========================================================
+// turb1600-derive — #[derive(TurbHash)]
+//
+// Generates `TurbHash::turb_hash_into` following the canonical
+// encoding documented in `turb1600::canonical`:
+//
+//   struct:  tag(TypeName) || field_0 || … || field_{n-1}
+//   enum:    tag(TypeName) || tag(VariantName) || fields…
+//
+// where tag(s) = LE64(|s|) || s. Fields are encoded in
+// declaration order; field names are not part of the encoding.
+// =========================================================
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Fields, GenericParam, Ident,
+};
+
+#[proc_macro_derive(TurbHash)]
+pub fn derive_turb_hash(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(ty) = param {
+            ty.bounds.push(parse_quote!(::turb1600::TurbHash));
+        }
+    }
+
+    let name = &input.ident;
+    let type_tag = name.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, fields) = destructure(&data.fields);
+            quote! {
+                let #name #pattern = self;
+                #fields
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|v| {
+                let variant = &v.ident;
+                let variant_tag = variant.to_string();
+                let (pattern, fields) = destructure(&v.fields);
+                quote! {
+                    #name::#variant #pattern => {
+                        ::turb1600::canonical::write_tag(hasher, #variant_tag);
+                        #fields
+                    }
+                }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(u) => {
+            return syn::Error::new_spanned(u.union_token, "TurbHash cannot be derived for unions")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    quote! {
+        impl #impl_generics ::turb1600::TurbHash for #name #ty_generics #where_clause {
+            fn turb_hash_into(&self, hasher: &mut ::turb1600::Turb1600) {
+                ::turb1600::canonical::write_tag(hasher, #type_tag);
+                #body
+            }
+        }
+    }
+    .into()
+}
+
+/// Build a pattern binding every field, and the statements that
+/// encode those bindings in declaration order.
+fn destructure(fields: &Fields) -> (TokenStream2, TokenStream2) {
+    let bindings: Vec<Ident> = (0..fields.len())
+        .map(|i| Ident::new(&format!("__field{}", i), Span::call_site()))
+        .collect();
+
+    let pattern = match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| f.ident.as_ref().unwrap());
+            quote!({ #(#names: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(( #(#bindings),* )),
+        Fields::Unit => quote!(),
+    };
+
+    let encode = quote! {
+        #(::turb1600::TurbHash::turb_hash_into(#bindings, hasher);)*
+    };
+
+    (pattern, encode)
+}
@@ -0,0 +1,168 @@
+// =========================================================
+// turb1600 — Permutation backends
+//
+// The sponge runs its rounds through one function pointer,
+// chosen once per process by `select` among:
+//
+//   portable-avx2  std::simd, compiled for AVX2   (feature `simd`, x86_64 with AVX2)
+//   portable       std::simd, baseline target     (feature `simd`)
+//   wasm-simd128   core::arch::wasm32             (wasm32 built with +simd128)
+//   scalar         core::permute
+//
+// Which one wins depends on the CPU (on x86_64 the baseline SSE2
+// build of the std::simd code is well behind the fused scalar
+// round, and the AVX2 one may or may not beat it), so when more
+// than one is available each is timed on a short burst of rounds
+// and the fastest is kept. This costs well under a millisecond,
+// once. Not every wasm32 host provides a clock, so there the most
+// specialised backend is taken untimed.
+//
+// All backends produce identical states. `TURB1600_BACKEND=<name>`
+// forces a specific one if it is available, e.g. to compare
+// backends or rule one out while debugging.
+// =========================================================
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::core::{permute, LANES};
+
+#[cfg(feature = "simd")]
+mod portable;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod wasm;
+
+/// Run `n` rounds starting at round index `first`.
+pub(crate) type RoundsFn = fn(&mut [u64; LANES], &mut [u64; LANES], usize, usize);
+
+pub(crate) struct Backend {
+    pub(crate) name: &'static str,
+    pub(crate) rounds: RoundsFn,
+}
+
+/// The scalar permutation needs no scratch lanes; the state is
+/// kept in a local across all `n` rounds.
+fn scalar_rounds(state: &mut [u64; LANES], _tmp: &mut [u64; LANES], first: usize, n: usize) {
+    let mut s = *state;
+    for round in first..first + n {
+        permute(&mut s, round);
+    }
+    *state = s;
+}
+
+/// Every backend this CPU can run, most specialised last.
+pub(crate) fn available() -> Vec<Backend> {
+    #[allow(unused_mut)]
+    let mut backends = vec![Backend {
+        name: "scalar",
+        rounds: scalar_rounds,
+    }];
+
+    #[cfg(feature = "simd")]
+    backends.push(Backend {
+        name: "portable",
+        rounds: portable::rounds,
+    });
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if std::is_x86_feature_detected!("avx2") {
+        backends.push(Backend {
+            name: "portable-avx2",
+            // SAFETY: AVX2 support was detected just above.
+            rounds: |s, t, first, n| unsafe { portable::rounds_avx2(s, t, first, n) },
+        });
+    }
+
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    backends.push(Backend {
+        name: "wasm-simd128",
+        rounds: wasm::rounds,
+    });
+
+    backends
+}
+
+/// Rounds per timing sample when calibrating
+const CALIBRATION_ROUNDS: usize = 1_000;
+
+/// Best of a few timed bursts of `CALIBRATION_ROUNDS` rounds.
+fn time_rounds(rounds: RoundsFn) -> Duration {
+    let mut state = [0x5a5a_5a5a_5a5a_5a5au64; LANES];
+    let mut tmp = [0u64; LANES];
+
+    (0..3)
+        .map(|_| {
+            let start = Instant::now();
+            rounds(&mut state, &mut tmp, 0, CALIBRATION_ROUNDS);
+            std::hint::black_box(&state);
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+static BACKEND: OnceLock<Backend> = OnceLock::new();
+
+fn select() -> &'static Backend {
+    BACKEND.get_or_init(|| {
+        let mut backends = available();
+        let forced = std::env::var("TURB1600_BACKEND").ok();
+        let pick = match forced.and_then(|name| backends.iter().position(|b| b.name == name)) {
+            Some(i) => i,
+            None if backends.len() == 1 => 0,
+            None if cfg!(target_arch = "wasm32") => backends.len() - 1,
+            None => (0..backends.len())
+                .min_by_key(|&i| time_rounds(backends[i].rounds))
+                .unwrap_or(0),
+        };
+        backends.swap_remove(pick)
+    })
+}
+
+/// Name of the permutation backend in use.
+pub fn backend_name() -> &'static str {
+    select().name
+}
+
+/// Names of the backends this CPU can run.
+pub fn available_backends() -> Vec<&'static str> {
+    available().into_iter().map(|b| b.name).collect()
+}
+
+/// Use the backend called `name` for the rest of the process.
+///
+/// Must run before anything is hashed. Returns `false`, changing
+/// nothing, if the backend is unavailable or one is already in use.
+pub fn set_backend(name: &str) -> bool {
+    match available().into_iter().find(|b| b.name == name) {
+        Some(backend) => BACKEND.set(backend).is_ok(),
+        None => false,
+    }
+}
+
+#[inline(always)]
+pub(crate) fn rounds(state: &mut [u64; LANES], tmp: &mut [u64; LANES], first: usize, n: usize) {
+    (select().rounds)(state, tmp, first, n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backends_agree() {
+        let mut seed = [0u64; LANES];
+        for (i, lane) in seed.iter_mut().enumerate() {
+            *lane = (i as u64 + 1).wrapping_mul(0x9E3779B97F4A7C15);
+        }
+
+        let mut expected = seed;
+        scalar_rounds(&mut expected, &mut [0u64; LANES], 5, 42);
+
+        for backend in available() {
+            let mut state = seed;
+            (backend.rounds)(&mut state, &mut [0u64; LANES], 5, 42);
+            assert_eq!(state, expected, "backend {}", backend.name);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+// =========================================================
+// turb1600 — std::simd permutation (feature `simd`, nightly)
+//
+// The state is held as five row vectors of u64x8, lanes 0..5 of
+// each carrying the row and lanes 5..8 padding. Column mixing,
+// the per-lane rotations and the nonlinear layer are whole-row
+// vector operations. The lane permutation shuffles the rows
+// spilled to memory with scalar loads, which measured faster
+// than a vector gather.
+// =========================================================
+
+use std::simd::{simd_swizzle, Simd};
+
+use crate::core::{round_constant, LANES, PERM_TABLE, ROT_TABLE};
+
+type Row = Simd<u64, 8>;
+
+const PAD: usize = 8;
+
+/// Row `x` gets `v[x+1]` (cyclic within the five row lanes)
+#[inline(always)]
+fn next1(v: Row) -> Row {
+    simd_swizzle!(v, [1, 2, 3, 4, 0, 5, 6, 7])
+}
+
+/// Row `x` gets `v[x+2]`
+#[inline(always)]
+fn next2(v: Row) -> Row {
+    simd_swizzle!(v, [2, 3, 4, 0, 1, 5, 6, 7])
+}
+
+/// Row `x` gets `v[x-1]`
+#[inline(always)]
+fn prev1(v: Row) -> Row {
+    simd_swizzle!(v, [4, 0, 1, 2, 3, 5, 6, 7])
+}
+
+const fn rot_rows() -> [[u64; PAD]; 5] {
+    let mut rows = [[0u64; PAD]; 5];
+    let mut i = 0;
+    while i < LANES {
+        rows[i / 5][i % 5] = ROT_TABLE[i] as u64;
+        i += 1;
+    }
+    rows
+}
+
+/// For each destination lane, where its source sits in the spilled
+/// (PAD-strided) rows. Only the first five entries of a row are used.
+const fn pi_gather() -> [[usize; PAD]; 5] {
+    let mut gather = [[0usize; PAD]; 5];
+    let mut src = 0;
+    while src < LANES {
+        let dst = PERM_TABLE[src];
+        gather[dst / 5][dst % 5] = (src / 5) * PAD + src % 5;
+        src += 1;
+    }
+    gather
+}
+
+const ROT_ROWS: [[u64; PAD]; 5] = rot_rows();
+const PI_GATHER: [[usize; PAD]; 5] = pi_gather();
+
+#[inline(always)]
+fn permute(rows: [Row; 5], round: usize) -> [Row; 5] {
+    // ---- column mixing ----
+    let c = rows[0] ^ rows[1] ^ rows[2] ^ rows[3] ^ rows[4];
+    let c1 = next1(c);
+    let d = prev1(c) ^ ((c1 << 1) | (c1 >> 63));
+
+    // ---- rotation ----
+    let k = Row::splat((round as u64 * 13) & 63);
+    let mask = Row::splat(63);
+    let mut spilled = [0u64; 5 * PAD];
+    for r in 0..5 {
+        let v = rows[r] ^ d;
+        let amt = (Row::from_array(ROT_ROWS[r]) + k) & mask;
+        let v = (v << amt) | (v >> ((Row::splat(64) - amt) & mask));
+        v.copy_to_slice(&mut spilled[r * PAD..(r + 1) * PAD]);
+    }
+
+    // ---- permutation + nonlinear layer ----
+    let mut out = [Row::splat(0); 5];
+    for r in 0..5 {
+        let g = &PI_GATHER[r];
+        let t = Row::from_array([
+            spilled[g[0]], spilled[g[1]], spilled[g[2]], spilled[g[3]], spilled[g[4]], 0, 0, 0,
+        ]);
+        out[r] = t ^ (!next1(t) & next2(t));
+    }
+
+    // ---- round injection ----
+    let i = (round * 7) % LANES;
+    out[i / 5].as_mut_array()[i % 5] ^= round_constant(round);
+    out
+}
+
+#[inline(always)]
+pub(super) fn rounds(state: &mut [u64; LANES], _tmp: &mut [u64; LANES], first: usize, n: usize) {
+    let mut rows = [Row::splat(0); 5];
+    for (r, row) in rows.iter_mut().enumerate() {
+        *row = Row::load_or_default(&state[r * 5..r * 5 + 5]);
+    }
+
+    for round in first..first + n {
+        rows = permute(rows, round);
+    }
+
+    for (r, row) in rows.iter().enumerate() {
+        state[r * 5..r * 5 + 5].copy_from_slice(&row.as_array()[..5]);
+    }
+}
+
+/// [`rounds`] compiled with AVX2 enabled.
+///
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+pub(super) unsafe fn rounds_avx2(
+    state: &mut [u64; LANES],
+    tmp: &mut [u64; LANES],
+    first: usize,
+    n: usize,
+) {
+    rounds(state, tmp, first, n)
+}
@@ -0,0 +1,81 @@
+// =========================================================
+// turb1600 — wasm32 SIMD128 permutation
+//
+// Built when compiling for wasm32 with `-C target-feature=+simd128`
+// (wasm has no runtime feature detection). Rows are handled as
+// lane pairs [x0 x1] [x2 x3] plus a scalar x4: column mixing and
+// the nonlinear layer run on i64x2 vectors, while the per-lane
+// rotations, which simd128 only offers with a uniform shift
+// count, stay scalar together with the lane permutation.
+// =========================================================
+
+use core::arch::wasm32::*;
+
+use crate::core::{rot_offset, round_constant, LANES, PERM_TABLE, ROT_TABLE};
+
+/// Load lanes `i` and `i + 1`.
+#[inline(always)]
+fn pair(s: &[u64; LANES], i: usize) -> v128 {
+    u64x2(s[i], s[i + 1])
+}
+
+#[inline(always)]
+fn store_pair(s: &mut [u64; LANES], i: usize, v: v128) {
+    s[i] = u64x2_extract_lane::<0>(v);
+    s[i + 1] = u64x2_extract_lane::<1>(v);
+}
+
+#[inline(always)]
+fn rotl1(v: v128) -> v128 {
+    v128_or(u64x2_shl(v, 1), u64x2_shr(v, 63))
+}
+
+#[inline(always)]
+fn permute(s: &mut [u64; LANES], t: &mut [u64; LANES], round: usize) {
+    // ---- column mixing ----
+    let mut c01 = pair(s, 0);
+    let mut c23 = pair(s, 2);
+    let mut c4 = s[4];
+    for row in (5..LANES).step_by(5) {
+        c01 = v128_xor(c01, pair(s, row));
+        c23 = v128_xor(c23, pair(s, row + 2));
+        c4 ^= s[row + 4];
+    }
+    let (c0, c1) = (u64x2_extract_lane::<0>(c01), u64x2_extract_lane::<1>(c01));
+    let (c2, c3) = (u64x2_extract_lane::<0>(c23), u64x2_extract_lane::<1>(c23));
+
+    let d01 = v128_xor(u64x2(c4, c0), rotl1(u64x2(c1, c2)));
+    let d23 = v128_xor(u64x2(c1, c2), rotl1(u64x2(c3, c4)));
+    let d4 = c3 ^ c0.rotate_left(1);
+
+    for row in (0..LANES).step_by(5) {
+        store_pair(s, row, v128_xor(pair(s, row), d01));
+        store_pair(s, row + 2, v128_xor(pair(s, row + 2), d23));
+        s[row + 4] ^= d4;
+    }
+
+    // ---- rotation + permutation ----
+    for i in 0..LANES {
+        t[PERM_TABLE[i]] = s[i].rotate_left(rot_offset(round, ROT_TABLE[i]));
+    }
+
+    // ---- nonlinear layer: x ^= !next1 & next2 ----
+    for row in (0..LANES).step_by(5) {
+        let t01 = pair(t, row);
+        let t23 = pair(t, row + 2);
+        let (t0, t1, t4) = (t[row], t[row + 1], t[row + 4]);
+
+        store_pair(s, row, v128_xor(t01, v128_andnot(t23, pair(t, row + 1))));
+        store_pair(s, row + 2, v128_xor(t23, v128_andnot(u64x2(t4, t0), pair(t, row + 3))));
+        s[row + 4] = t4 ^ (!t0 & t1);
+    }
+
+    // ---- round injection ----
+    s[(round * 7) % LANES] ^= round_constant(round);
+}
+
+pub(super) fn rounds(state: &mut [u64; LANES], tmp: &mut [u64; LANES], first: usize, n: usize) {
+    for round in first..first + n {
+        permute(state, tmp, round);
+    }
+}
@@ -0,0 +1,187 @@
+// =========================================================
+// turb1600 — Runtime benchmarking
+//
+// `measure` times the hashing paths on synthetic data so an
+// application can size its buffers, or choose a permutation
+// backend, from numbers taken on the machine it runs on.
+//
+// Throughput is in bytes of message absorbed per second. For the
+// permutation alone a block's worth of rounds (ROUNDS_MAIN) is
+// counted as BLOCK_BYTES bytes. Cycles are read from the TSC on
+// x86_64, which ticks at a fixed reference rate rather than the
+// current core clock; elsewhere they are not reported.
+// =========================================================
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::backend;
+use crate::core::{Turb1600, BLOCK_BYTES, LANES, ROUNDS_MAIN};
+
+/// What to measure.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Message length for the one-shot and streaming runs.
+    pub data_len: usize,
+    /// `update` sizes to try for the streaming runs.
+    pub stream_chunks: Vec<usize>,
+    /// Minimum wall time spent on each measurement.
+    pub min_duration: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            data_len: 1 << 20,
+            stream_chunks: vec![64, 1 << 10, 16 << 10, 1 << 20],
+            min_duration: Duration::from_millis(50),
+        }
+    }
+}
+
+/// One timed run.
+#[derive(Debug, Clone, Copy)]
+pub struct Measurement {
+    pub bytes: u64,
+    pub elapsed: Duration,
+    /// TSC ticks, where available.
+    pub cycles: Option<u64>,
+}
+
+impl Measurement {
+    pub fn bytes_per_sec(&self) -> f64 {
+        self.bytes as f64 / self.elapsed.as_secs_f64()
+    }
+
+    pub fn bytes_per_cycle(&self) -> Option<f64> {
+        self.cycles.map(|c| self.bytes as f64 / c as f64)
+    }
+}
+
+/// Results of [`measure`].
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// Backend the hashing paths ran on.
+    pub backend: &'static str,
+    /// Permutation throughput of every available backend.
+    pub permutation: Vec<(&'static str, Measurement)>,
+    pub one_shot: Measurement,
+    /// Streaming throughput per `update` size, as in the config.
+    pub streaming: Vec<(usize, Measurement)>,
+}
+
+#[cfg(target_arch = "x86_64")]
+fn cycles() -> Option<u64> {
+    // SAFETY: RDTSC is available on every x86_64 CPU.
+    #[allow(unused_unsafe)]
+    Some(unsafe { std::arch::x86_64::_rdtsc() })
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn cycles() -> Option<u64> {
+    None
+}
+
+/// Repeat `run` (which processes `bytes_per_run` bytes) until
+/// `min_duration` has passed.
+fn time(min_duration: Duration, bytes_per_run: u64, mut run: impl FnMut()) -> Measurement {
+    run(); // warm-up
+
+    let (start, start_cycles) = (Instant::now(), cycles());
+    let mut bytes = 0;
+    loop {
+        run();
+        bytes += bytes_per_run;
+        if start.elapsed() >= min_duration {
+            break;
+        }
+    }
+    let elapsed = start.elapsed();
+    let cycles = match (start_cycles, cycles()) {
+        (Some(a), Some(b)) => Some(b.wrapping_sub(a)),
+        _ => None,
+    };
+
+    Measurement {
+        bytes,
+        elapsed,
+        cycles,
+    }
+}
+
+/// Time the permutation, one-shot and streaming paths.
+///
+/// ```no_run
+/// use turb1600::bench::{measure, Config};
+///
+/// let report = measure(&Config::default());
+/// let (chunk, _) = report
+///     .streaming
+///     .iter()
+///     .max_by(|a, b| a.1.bytes_per_sec().total_cmp(&b.1.bytes_per_sec()))
+///     .unwrap();
+/// println!("{} via {}; best update size {}", report.one_shot.bytes_per_sec(), report.backend, chunk);
+/// ```
+pub fn measure(config: &Config) -> Report {
+    let data: Vec<u8> = (0..config.data_len).map(|i| (i * 31 + 7) as u8).collect();
+    let len = config.data_len as u64;
+
+    let permutation = backend::available()
+        .into_iter()
+        .map(|b| {
+            let mut state = [0u64; LANES];
+            let mut tmp = [0u64; LANES];
+            let m = time(config.min_duration, BLOCK_BYTES as u64 * 64, || {
+                (b.rounds)(&mut state, &mut tmp, 0, ROUNDS_MAIN * 64);
+                black_box(&state);
+            });
+            (b.name, m)
+        })
+        .collect();
+
+    let one_shot = time(config.min_duration, len, || {
+        black_box(crate::turb1600_hash(black_box(&data)));
+    });
+
+    let streaming = config
+        .stream_chunks
+        .iter()
+        .map(|&chunk| {
+            let m = time(config.min_duration, len, || {
+                let mut hasher = Turb1600::new();
+                for part in data.chunks(chunk.max(1)) {
+                    hasher.update(black_box(part));
+                }
+                black_box(hasher.finalize());
+            });
+            (chunk, m)
+        })
+        .collect();
+
+    Report {
+        backend: backend::backend_name(),
+        permutation,
+        one_shot,
+        streaming,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_reports_every_path() {
+        let config = Config {
+            data_len: 4096,
+            stream_chunks: vec![1, 100],
+            min_duration: Duration::from_millis(1),
+        };
+        let report = measure(&config);
+
+        assert!(report.permutation.iter().any(|(name, _)| *name == "scalar"));
+        assert_eq!(report.streaming.len(), 2);
+        assert!(report.one_shot.bytes >= 4096);
+        assert!(report.one_shot.bytes_per_sec() > 0.0);
+    }
+}
@@ -0,0 +1,325 @@
+// =========================================================
+// turb1600 — Hasher configuration
+//
+// `Turb1600::builder()` is the single entry point for keyed,
+// salted, personalized and variable-length hashing:
+//
+//   no options       plain turb1600 (same as `Turb1600::new`)
+//   key only         keyed MAC (same as `Turb1600::new_keyed`)
+//   anything else    parameter IV, then the parameter block
+//
+//     LE64(out_len) || frame(key) || frame(salt) || frame(personal)
+//
+// zero-padded to whole rate blocks (up to 288 bytes, so as many
+// as three). frame(x) = LE64(|x|) || x, and an absent option is
+// empty; present options must be non-empty, so every encoding is
+// unambiguous. Output lengths other than DIGEST_BYTES are bound
+// into the block, so a short digest is not a prefix of the full
+// one. XOF mode encodes out_len as 0, which no fixed length uses.
+// =========================================================
+
+use std::fmt;
+
+use crate::core::{Sponge, Turb1600, DIGEST_BYTES, PARAM_TAG};
+use crate::key::SecretKey;
+use crate::mac::keyed_sponge;
+#[cfg(feature = "getrandom")]
+use crate::salt::{generate_salt, SALT_BYTES};
+
+/// Largest accepted key, in bytes.
+pub const MAX_KEY_BYTES: usize = 128;
+/// Largest accepted salt, in bytes.
+pub const MAX_SALT_BYTES: usize = 64;
+/// Largest accepted personalization string, in bytes.
+pub const MAX_PERSONAL_BYTES: usize = 64;
+/// Largest accepted [`output_len`](Turb1600Builder::output_len), in
+/// bytes (1 MiB); [`xof`](Turb1600Builder::xof) has no limit.
+pub const MAX_OUTPUT_LEN: usize = 1 << 20;
+
+/// Rejected builder configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// A key was given but is empty.
+    EmptyKey,
+    /// The key exceeds [`MAX_KEY_BYTES`].
+    KeyTooLong(usize),
+    /// The salt exceeds [`MAX_SALT_BYTES`].
+    SaltTooLong(usize),
+    /// The personalization exceeds [`MAX_PERSONAL_BYTES`].
+    PersonalTooLong(usize),
+    /// A salt was given but is empty.
+    EmptySalt,
+    /// A personalization was given but is empty.
+    EmptyPersonal,
+    /// The output length is zero.
+    ZeroOutputLen,
+    /// The output length exceeds [`MAX_OUTPUT_LEN`].
+    OutputLenTooLong(usize),
+    /// Both a fixed output length and XOF mode were requested.
+    OutputLenWithXof,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::EmptyKey => f.write_str("key must not be empty"),
+            BuildError::KeyTooLong(n) => {
+                write!(f, "key is {} bytes, at most {} allowed", n, MAX_KEY_BYTES)
+            }
+            BuildError::SaltTooLong(n) => {
+                write!(f, "salt is {} bytes, at most {} allowed", n, MAX_SALT_BYTES)
+            }
+            BuildError::PersonalTooLong(n) => write!(
+                f,
+                "personalization is {} bytes, at most {} allowed",
+                n, MAX_PERSONAL_BYTES
+            ),
+            BuildError::EmptySalt => f.write_str("salt must not be empty"),
+            BuildError::EmptyPersonal => f.write_str("personalization must not be empty"),
+            BuildError::ZeroOutputLen => f.write_str("output length must be non-zero"),
+            BuildError::OutputLenTooLong(n) => write!(
+                f,
+                "output length is {} bytes, at most {} allowed (use xof() for more)",
+                n, MAX_OUTPUT_LEN
+            ),
+            BuildError::OutputLenWithXof => {
+                f.write_str("a fixed output length cannot be combined with XOF mode")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Builder for configured [`Turb1600`] hashers.
+#[derive(Clone, Default)]
+pub struct Turb1600Builder<'a> {
+    key: Option<&'a SecretKey>,
+    salt: Option<&'a [u8]>,
+    /// Set by `salt_or_random` when no salt was given
+    #[cfg(feature = "getrandom")]
+    random_salt: Option<[u8; SALT_BYTES]>,
+    personal: Option<&'a [u8]>,
+    output_len: Option<usize>,
+    xof: bool,
+}
+
+impl<'a> Turb1600Builder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Key the hash (MAC mode).
+    pub fn key(mut self, key: &'a SecretKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Randomize the hash with a salt.
+    pub fn salt(mut self, salt: &'a [u8]) -> Self {
+        self.salt = Some(salt);
+        #[cfg(feature = "getrandom")]
+        {
+            self.random_salt = None;
+        }
+        self
+    }
+
+    /// Use `salt`, or a fresh [`SALT_BYTES`]-byte salt from the OS RNG
+    /// when it is `None`. Read it back with
+    /// [`salt_bytes`](Self::salt_bytes) to store it: the same salt
+    /// is needed to reproduce the hash.
+    #[cfg(feature = "getrandom")]
+    pub fn salt_or_random(mut self, salt: Option<&'a [u8]>) -> Self {
+        match salt {
+            Some(salt) => self = self.salt(salt),
+            None => {
+                self.salt = None;
+                self.random_salt = Some(generate_salt());
+            }
+        }
+        self
+    }
+
+    /// The salt the hasher will use, if any.
+    pub fn salt_bytes(&self) -> Option<&[u8]> {
+        #[cfg(feature = "getrandom")]
+        if let Some(random) = &self.random_salt {
+            return Some(random);
+        }
+        self.salt
+    }
+
+    /// Separate applications with a personalization string.
+    pub fn personal(mut self, personal: &'a [u8]) -> Self {
+        self.personal = Some(personal);
+        self
+    }
+
+    /// Digest length returned by `finalize`, in bytes, at most
+    /// [`MAX_OUTPUT_LEN`]. For longer or open-ended output use
+    /// [`xof`](Self::xof).
+    pub fn output_len(mut self, len: usize) -> Self {
+        self.output_len = Some(len);
+        self
+    }
+
+    /// Extendable-output mode: read any amount of output with
+    /// [`Turb1600::finalize_xof`]. The mode is bound into the IV, so
+    /// the stream differs from every fixed-length configuration;
+    /// `finalize` returns its first [`DIGEST_BYTES`].
+    pub fn xof(mut self) -> Self {
+        self.xof = true;
+        self
+    }
+
+    fn validate(&self) -> Result<(), BuildError> {
+        if let Some(key) = self.key {
+            if key.is_empty() {
+                return Err(BuildError::EmptyKey);
+            }
+            if key.len() > MAX_KEY_BYTES {
+                return Err(BuildError::KeyTooLong(key.len()));
+            }
+        }
+        if let Some(salt) = self.salt_bytes() {
+            if salt.is_empty() {
+                return Err(BuildError::EmptySalt);
+            }
+            if salt.len() > MAX_SALT_BYTES {
+                return Err(BuildError::SaltTooLong(salt.len()));
+            }
+        }
+        if let Some(p) = self.personal {
+            if p.is_empty() {
+                return Err(BuildError::EmptyPersonal);
+            }
+            if p.len() > MAX_PERSONAL_BYTES {
+                return Err(BuildError::PersonalTooLong(p.len()));
+            }
+        }
+        match self.output_len {
+            Some(0) => Err(BuildError::ZeroOutputLen),
+            Some(n) if n > MAX_OUTPUT_LEN => Err(BuildError::OutputLenTooLong(n)),
+            Some(_) if self.xof => Err(BuildError::OutputLenWithXof),
+            _ => Ok(()),
+        }
+    }
+
+    /// Validate the options and create the hasher.
+    pub fn build(&self) -> Result<Turb1600, BuildError> {
+        self.validate()?;
+
+        let out_len = self.output_len.unwrap_or(DIGEST_BYTES);
+        let parameterized = self.salt_bytes().is_some()
+            || self.personal.is_some()
+            || out_len != DIGEST_BYTES
+            || self.xof;
+        let bound_len = if self.xof { 0 } else { out_len as u64 };
+
+        let sponge = match self.key {
+            _ if parameterized => {
+                let mut sponge = Sponge::with_tag(PARAM_TAG);
+                sponge.update(&bound_len.to_le_bytes());
+                for field in [
+                    self.key.map_or(&[][..], SecretKey::expose_secret),
+                    self.salt_bytes().unwrap_or_default(),
+                    self.personal.unwrap_or_default(),
+                ] {
+                    sponge.update(&(field.len() as u64).to_le_bytes());
+                    sponge.update(field);
+                }
+                sponge.pad_block();
+                sponge
+            }
+            Some(key) => keyed_sponge(key),
+            None => Sponge::new(),
+        };
+
+        Ok(Turb1600::from_parts(sponge, out_len))
+    }
+}
+
+impl Turb1600 {
+    /// Start configuring a hasher.
+    pub fn builder<'a>() -> Turb1600Builder<'a> {
+        Turb1600Builder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(builder: Turb1600Builder, msg: &[u8]) -> Vec<u8> {
+        let mut h = builder.build().unwrap();
+        h.update(msg);
+        h.finalize()
+    }
+
+    #[test]
+    fn test_builder_defaults_match_existing_modes() {
+        let key = SecretKey::new(b"key");
+        assert_eq!(digest(Turb1600::builder(), b"m"), crate::turb1600_hash(b"m"));
+        assert_eq!(
+            digest(Turb1600::builder().key(&key), b"m"),
+            crate::turb1600_mac(&key, b"m")
+        );
+    }
+
+    #[test]
+    fn test_builder_binds_parameters() {
+        let full = digest(Turb1600::builder().salt(b"s"), b"m");
+        let short = digest(Turb1600::builder().salt(b"s").output_len(32), b"m");
+        assert_eq!(short.len(), 32);
+        assert_ne!(&full[..32], &short[..]);
+        assert_ne!(full, digest(Turb1600::builder().personal(b"s"), b"m"));
+    }
+
+    #[test]
+    fn test_builder_xof_mode() {
+        let xof = |builder: Turb1600Builder, len| {
+            let mut h = builder.build().unwrap();
+            h.update(b"m");
+            let mut out = vec![0u8; len];
+            h.finalize_xof().squeeze(&mut out);
+            out
+        };
+        let long = xof(Turb1600::builder().personal(b"p").xof(), 300);
+        assert_eq!(xof(Turb1600::builder().personal(b"p").xof(), 40), &long[..40]);
+        assert_eq!(digest(Turb1600::builder().personal(b"p").xof(), b"m"), &long[..DIGEST_BYTES]);
+        // Not the stream of any fixed-length configuration
+        assert_ne!(xof(Turb1600::builder().personal(b"p"), 300), long);
+        assert_ne!(xof(Turb1600::builder().personal(b"p").output_len(300), 300), long);
+        assert_ne!(xof(Turb1600::builder().xof(), 300), xof(Turb1600::builder(), 300));
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid() {
+        let empty = SecretKey::new(b"");
+        assert_eq!(Turb1600::builder().key(&empty).build().err(), Some(BuildError::EmptyKey));
+        assert_eq!(
+            Turb1600::builder().salt(&[0; 65]).build().err(),
+            Some(BuildError::SaltTooLong(65))
+        );
+        assert_eq!(
+            Turb1600::builder().output_len(0).build().err(),
+            Some(BuildError::ZeroOutputLen)
+        );
+        // Empty values would encode like absent ones
+        assert_eq!(Turb1600::builder().salt(b"").build().err(), Some(BuildError::EmptySalt));
+        assert_eq!(
+            Turb1600::builder().personal(b"").build().err(),
+            Some(BuildError::EmptyPersonal)
+        );
+        assert_eq!(
+            Turb1600::builder().output_len(32).xof().build().err(),
+            Some(BuildError::OutputLenWithXof)
+        );
+        assert!(Turb1600::builder().output_len(MAX_OUTPUT_LEN).build().is_ok());
+        assert_eq!(
+            Turb1600::builder().output_len(usize::MAX).build().err(),
+            Some(BuildError::OutputLenTooLong(usize::MAX))
+        );
+    }
+}
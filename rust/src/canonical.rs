@@ -0,0 +1,201 @@
+// =========================================================
+// turb1600 — Canonical hashing of structured values
+//
+// `TurbHash` feeds a value into a hasher using a fixed,
+// platform-independent encoding:
+//
+//   integers        fixed-width little-endian
+//                   (usize/isize widened to 64 bits)
+//   bool            one byte, 0 or 1
+//   char            LE32 code point
+//   str, [T], Vec   LE64(len) || elements
+//   [T; N]          elements (the length is part of the type)
+//   Option<T>       0x00 | 0x01 || value
+//   tuples          elements in order
+//
+// `#[derive(TurbHash)]` (feature `derive`) prefixes structs
+// and enums with a type tag and enum variants with a variant
+// tag, then encodes fields in declaration order. A tag is
+// LE64(len) || UTF-8 name.
+// =========================================================
+
+use crate::core::Turb1600;
+
+/// Values with a canonical turb1600 content hash.
+pub trait TurbHash {
+    /// Feed the canonical encoding of `self` into `hasher`.
+    fn turb_hash_into(&self, hasher: &mut Turb1600);
+
+    /// Digest of the canonical encoding of `self`.
+    fn turb_hash(&self) -> Vec<u8> {
+        let mut hasher = Turb1600::new();
+        self.turb_hash_into(&mut hasher);
+        hasher.finalize()
+    }
+}
+
+/// Write a length-framed type or variant name.
+pub fn write_tag(hasher: &mut Turb1600, tag: &str) {
+    write_len(hasher, tag.len());
+    hasher.update(tag.as_bytes());
+}
+
+#[inline]
+fn write_len(hasher: &mut Turb1600, len: usize) {
+    hasher.update(&(len as u64).to_le_bytes());
+}
+
+macro_rules! impl_int {
+    ($($t:ty => $w:ty),* $(,)?) => {
+        $(
+            impl TurbHash for $t {
+                fn turb_hash_into(&self, hasher: &mut Turb1600) {
+                    hasher.update(&(*self as $w).to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_int!(
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => u64,
+    i8 => i8, i16 => i16, i32 => i32, i64 => i64, i128 => i128, isize => i64,
+);
+
+impl TurbHash for bool {
+    fn turb_hash_into(&self, hasher: &mut Turb1600) {
+        hasher.update(&[*self as u8]);
+    }
+}
+
+impl TurbHash for char {
+    fn turb_hash_into(&self, hasher: &mut Turb1600) {
+        hasher.update(&(*self as u32).to_le_bytes());
+    }
+}
+
+impl TurbHash for str {
+    fn turb_hash_into(&self, hasher: &mut Turb1600) {
+        write_len(hasher, self.len());
+        hasher.update(self.as_bytes());
+    }
+}
+
+impl TurbHash for String {
+    fn turb_hash_into(&self, hasher: &mut Turb1600) {
+        self.as_str().turb_hash_into(hasher);
+    }
+}
+
+impl<T: TurbHash> TurbHash for [T] {
+    fn turb_hash_into(&self, hasher: &mut Turb1600) {
+        write_len(hasher, self.len());
+        for item in self {
+            item.turb_hash_into(hasher);
+        }
+    }
+}
+
+impl<T: TurbHash> TurbHash for Vec<T> {
+    fn turb_hash_into(&self, hasher: &mut Turb1600) {
+        self.as_slice().turb_hash_into(hasher);
+    }
+}
+
+impl<T: TurbHash, const N: usize> TurbHash for [T; N] {
+    fn turb_hash_into(&self, hasher: &mut Turb1600) {
+        for item in self {
+            item.turb_hash_into(hasher);
+        }
+    }
+}
+
+impl<T: TurbHash> TurbHash for Option<T> {
+    fn turb_hash_into(&self, hasher: &mut Turb1600) {
+        match self {
+            None => hasher.update(&[0]),
+            Some(v) => {
+                hasher.update(&[1]);
+                v.turb_hash_into(hasher);
+            }
+        }
+    }
+}
+
+impl<T: TurbHash + ?Sized> TurbHash for &T {
+    fn turb_hash_into(&self, hasher: &mut Turb1600) {
+        (**self).turb_hash_into(hasher);
+    }
+}
+
+impl<T: TurbHash + ?Sized> TurbHash for Box<T> {
+    fn turb_hash_into(&self, hasher: &mut Turb1600) {
+        (**self).turb_hash_into(hasher);
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident)+) => {
+        impl<$($name: TurbHash),+> TurbHash for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn turb_hash_into(&self, hasher: &mut Turb1600) {
+                let ($($name,)+) = self;
+                $($name.turb_hash_into(hasher);)+
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A B);
+impl_tuple!(A B C);
+impl_tuple!(A B C D);
+impl_tuple!(A B C D E);
+impl_tuple!(A B C D E F);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framing_separates_fields() {
+        let a = ("ab".to_string(), "c".to_string()).turb_hash();
+        let b = ("a".to_string(), "bc".to_string()).turb_hash();
+        assert_ne!(a, b);
+        assert_ne!(Some(0u8).turb_hash(), None::<u8>.turb_hash());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive() {
+        use crate::TurbHash;
+
+        #[derive(TurbHash)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        #[derive(TurbHash)]
+        struct Pair(i32, i32);
+
+        #[derive(TurbHash)]
+        enum Shape<T> {
+            Dot,
+            Line(T, T),
+        }
+
+        let p = Point { x: 1, y: 2 };
+        let mut h = Turb1600::new();
+        write_tag(&mut h, "Point");
+        1i32.turb_hash_into(&mut h);
+        2i32.turb_hash_into(&mut h);
+        assert_eq!(p.turb_hash(), h.finalize());
+
+        assert_ne!(p.turb_hash(), Pair(1, 2).turb_hash());
+        assert_ne!(
+            Shape::<u8>::Dot.turb_hash(),
+            Shape::Line(0u8, 0).turb_hash()
+        );
+    }
+}
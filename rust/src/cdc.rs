@@ -0,0 +1,241 @@
+// =========================================================
+// turb1600 — Content-defined chunking (FastCDC)
+//
+// Cuts a stream at boundaries chosen by its content rather than
+// by offset, so inserting or deleting bytes only changes the
+// chunks around the edit and the rest still deduplicate.
+//
+// Boundaries follow FastCDC: a gear hash rolls over the input,
+//
+//   h = (h << 1) + GEAR[byte]
+//
+// and a chunk ends where `h & mask == 0`. No cut is made before
+// `min_size`. Up to `avg_size` a stricter mask (one more bit
+// than log2(avg_size)) is used, and after that a looser one (one
+// bit fewer), which pulls the chunk sizes towards the average.
+// Chunks are forced to end at `max_size`. Masks take the top
+// bits of `h`, which depend on the last 64 input bytes.
+//
+// GEAR is the splitmix64 sequence seeded with "turb1600" as a
+// big-endian u64. Every chunk carries its plain turb1600 digest.
+// =========================================================
+
+use std::io::{self, Read};
+
+use crate::output::Digest;
+
+/// Default minimum chunk size (16 KiB).
+pub const CDC_MIN_DEFAULT: usize = 16 << 10;
+/// Default target average chunk size (64 KiB).
+pub const CDC_AVG_DEFAULT: usize = 64 << 10;
+/// Default maximum chunk size (256 KiB).
+pub const CDC_MAX_DEFAULT: usize = 256 << 10;
+
+const GEAR_SEED: u64 = u64::from_be_bytes(*b"turb1600");
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state = GEAR_SEED;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+static GEAR: [u64; 256] = gear_table();
+
+/// Mask selecting the top `bits` bits of the gear hash.
+fn top_bits(bits: u32) -> u64 {
+    match bits {
+        0 => 0,
+        b => !0u64 << (64 - b.min(64)),
+    }
+}
+
+/// One content-defined chunk of a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chunk {
+    /// Position of the first byte in the stream.
+    pub offset: u64,
+    /// Length in bytes.
+    pub length: usize,
+    /// Plain turb1600 digest of the chunk's bytes.
+    pub digest: Digest,
+}
+
+/// FastCDC chunker with fixed size bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chunker {
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    mask_strict: u64,
+    mask_loose: u64,
+}
+
+impl Chunker {
+    /// Panics unless `0 < min_size <= avg_size <= max_size`.
+    pub fn new(min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        assert!(
+            min_size > 0 && min_size <= avg_size && avg_size <= max_size,
+            "chunk sizes must satisfy 0 < min <= avg <= max"
+        );
+        let bits = avg_size.ilog2();
+        Self {
+            min_size,
+            avg_size,
+            max_size,
+            mask_strict: top_bits(bits + 1),
+            mask_loose: top_bits(bits.saturating_sub(1)),
+        }
+    }
+
+    pub fn min_size(&self) -> usize {
+        self.min_size
+    }
+
+    pub fn avg_size(&self) -> usize {
+        self.avg_size
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Length of the chunk starting at `data[0]`, treating the end
+    /// of `data` as the end of the stream.
+    pub fn cut(&self, data: &[u8]) -> usize {
+        if data.len() <= self.min_size {
+            return data.len();
+        }
+        let end = data.len().min(self.max_size);
+        let center = end.min(self.avg_size);
+
+        let mut hash = 0u64;
+        for (i, &b) in data.iter().enumerate().take(center).skip(self.min_size) {
+            hash = (hash << 1).wrapping_add(GEAR[b as usize]);
+            if hash & self.mask_strict == 0 {
+                return i;
+            }
+        }
+        for (i, &b) in data.iter().enumerate().take(end).skip(center) {
+            hash = (hash << 1).wrapping_add(GEAR[b as usize]);
+            if hash & self.mask_loose == 0 {
+                return i;
+            }
+        }
+        end
+    }
+
+    /// Split `data` into chunks and hash each one.
+    pub fn chunks(&self, data: &[u8]) -> Vec<Chunk> {
+        let mut chunks = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let length = self.cut(&data[offset..]);
+            chunks.push(Chunk {
+                offset: offset as u64,
+                length,
+                digest: Digest::of(&data[offset..offset + length]),
+            });
+            offset += length;
+        }
+        chunks
+    }
+
+    /// Like [`chunks`](Self::chunks), reading `reader` to EOF with
+    /// at most two maximum-size chunks buffered.
+    pub fn chunk_reader<R: Read>(&self, mut reader: R) -> io::Result<Vec<Chunk>> {
+        let mut buf = vec![0u8; 2 * self.max_size];
+        let (mut start, mut end) = (0, 0);
+        let mut offset = 0u64;
+        let mut eof = false;
+        let mut chunks = Vec::new();
+
+        loop {
+            // A cut needs either a full `max_size` window or the true end
+            if !eof && end - start < self.max_size {
+                buf.copy_within(start..end, 0);
+                end -= start;
+                start = 0;
+                while end < buf.len() {
+                    match reader.read(&mut buf[end..]) {
+                        Ok(0) => {
+                            eof = true;
+                            break;
+                        }
+                        Ok(n) => end += n,
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            if start == end {
+                return Ok(chunks);
+            }
+
+            let length = self.cut(&buf[start..end]);
+            chunks.push(Chunk {
+                offset,
+                length,
+                digest: Digest::of(&buf[start..start + length]),
+            });
+            start += length;
+            offset += length as u64;
+        }
+    }
+}
+
+impl Default for Chunker {
+    fn default() -> Self {
+        Self::new(CDC_MIN_DEFAULT, CDC_AVG_DEFAULT, CDC_MAX_DEFAULT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        crate::Turb1600::new().finalize_xof().squeeze(&mut data);
+        data
+    }
+
+    #[test]
+    fn test_chunks_respect_bounds_and_cover_input() {
+        let data = stream(200_000);
+        let chunker = Chunker::new(256, 1024, 4096);
+        let chunks = chunker.chunks(&data);
+
+        let mut offset = 0;
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.offset, offset as u64);
+            assert!(chunk.length <= 4096);
+            assert!(chunk.length >= 256 || i == chunks.len() - 1);
+            assert_eq!(chunk.digest, Digest::of(&data[offset..offset + chunk.length]));
+            offset += chunk.length;
+        }
+        assert_eq!(offset, data.len());
+        assert_eq!(chunker.chunk_reader(&data[..]).unwrap(), chunks);
+    }
+
+    #[test]
+    fn test_insertion_only_disturbs_nearby_chunks() {
+        let data = stream(200_000);
+        let mut edited = data.clone();
+        edited.splice(50_000..50_000, *b"inserted");
+
+        let chunker = Chunker::new(256, 1024, 4096);
+        let before: Vec<Digest> = chunker.chunks(&data).iter().map(|c| c.digest).collect();
+        let after = chunker.chunks(&edited);
+        let shared = after.iter().filter(|c| before.contains(&c.digest)).count();
+        assert!(shared + 4 >= after.len(), "{} of {} chunks shared", shared, after.len());
+    }
+}
@@ -0,0 +1,167 @@
+// =========================================================
+// turb1600 CLI — selectable algorithms (`--algorithm`)
+//
+// Digests from algorithms other than plain turb1600 carry the
+// algorithm name as a prefix in output lines and manifests:
+//
+//   <hex>                 turb1600
+//   <name>:<hex>          any other algorithm
+//
+// so a single manifest can mix algorithms and `--check` still
+// knows how to verify each line. turb1600-256 and turb1600-512
+// are the reduced-output presets. Tree mode names its chunk size
+// unless it is the default: `turb1600-tree` (1 MiB leaves),
+// `turb1600-tree-4MiB`, `turb1600-tree-65536`, …
+// =========================================================
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use turb1600::file::FILE_BUFFER_DEFAULT;
+use turb1600::{turb1600_tree_hash, Turb1600, TREE_CHUNK_DEFAULT};
+
+use super::parse_size;
+
+const TREE_NAME: &str = "turb1600-tree";
+
+/// Largest accepted tree leaf (1 GiB). Tree mode holds two batches of
+/// one leaf per core in memory, so names from untrusted manifests
+/// must not pick an arbitrary size.
+pub const TREE_CHUNK_MAX: usize = 1 << 30;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Algorithm {
+    /// The standard 128-byte digest
+    Turb1600,
+    /// The 32-byte preset
+    Turb256,
+    /// The 64-byte preset
+    Turb512,
+    /// Tree mode with leaves of the given size
+    Tree(usize),
+}
+
+/// `n` with the largest binary unit that divides it exactly
+pub(super) fn size_name(n: usize) -> String {
+    for (shift, unit) in [(30, "GiB"), (20, "MiB"), (10, "KiB")] {
+        if n >> shift > 0 && n.trailing_zeros() >= shift {
+            return format!("{}{}", n >> shift, unit);
+        }
+    }
+    n.to_string()
+}
+
+/// Stream a file through `hasher` with a `buf_size`-byte buffer
+fn hash_file_with(mut hasher: Turb1600, path: &Path, buf_size: usize) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; buf_size.max(1)];
+    loop {
+        match file.read(&mut buf) {
+            Ok(0) => return Ok(hasher.finalize()),
+            Ok(n) => hasher.update(&buf[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+impl Algorithm {
+    pub fn name(self) -> String {
+        match self {
+            Algorithm::Turb1600 => "turb1600".to_string(),
+            Algorithm::Turb256 => "turb1600-256".to_string(),
+            Algorithm::Turb512 => "turb1600-512".to_string(),
+            Algorithm::Tree(TREE_CHUNK_DEFAULT) => TREE_NAME.to_string(),
+            Algorithm::Tree(chunk) => format!("{}-{}", TREE_NAME, size_name(chunk)),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "turb1600" => Some(Algorithm::Turb1600),
+            "turb1600-256" => Some(Algorithm::Turb256),
+            "turb1600-512" => Some(Algorithm::Turb512),
+            TREE_NAME => Some(Algorithm::Tree(TREE_CHUNK_DEFAULT)),
+            _ => {
+                let chunk = parse_size(name.strip_prefix(TREE_NAME)?.strip_prefix('-')?)?;
+                usize::try_from(chunk)
+                    .ok()
+                    .filter(|&c| c > 0 && c <= TREE_CHUNK_MAX)
+                    .map(Algorithm::Tree)
+            }
+        }
+    }
+
+    pub fn hash(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Algorithm::Turb1600 => turb1600::turb1600_hash(data),
+            Algorithm::Turb256 => turb1600::turb1600_256_hash(data).to_vec(),
+            Algorithm::Turb512 => turb1600::turb1600_512_hash(data).to_vec(),
+            Algorithm::Tree(chunk) => turb1600_tree_hash(data, chunk),
+        }
+    }
+
+    pub fn hash_file<P: AsRef<Path>>(self, path: P) -> io::Result<Vec<u8>> {
+        self.hash_file_buffered(path, FILE_BUFFER_DEFAULT)
+    }
+
+    /// Stream a file through `buf_size`-byte reads (tree mode reads
+    /// whole leaves instead, hashing them on every core when built
+    /// with `threads`), so memory use stays constant.
+    pub fn hash_file_buffered<P: AsRef<Path>>(self, path: P, buf_size: usize) -> io::Result<Vec<u8>> {
+        match self {
+            Algorithm::Turb1600 => turb1600::hash_file_buffered(path, buf_size),
+            Algorithm::Turb256 => hash_file_with(turb1600::turb1600_256(), path.as_ref(), buf_size),
+            Algorithm::Turb512 => hash_file_with(turb1600::turb1600_512(), path.as_ref(), buf_size),
+            #[cfg(feature = "threads")]
+            Algorithm::Tree(chunk) => turb1600::turb1600_tree_hash_reader_parallel(File::open(path)?, chunk, 0),
+            #[cfg(not(feature = "threads"))]
+            Algorithm::Tree(chunk) => turb1600::turb1600_tree_hash_reader(File::open(path)?, chunk),
+        }
+    }
+
+    /// `digest` in hex, prefixed with `<name>:` unless this is turb1600.
+    pub fn tag(self, digest: &[u8]) -> String {
+        match self {
+            Algorithm::Turb1600 => hex::encode(digest),
+            _ => format!("{}:{}", self.name(), hex::encode(digest)),
+        }
+    }
+
+    /// Split a digest written by [`tag`](Self::tag) into its algorithm
+    /// and raw bytes. `None` for unknown names or malformed hex.
+    pub fn untag(s: &str) -> Option<(Self, Vec<u8>)> {
+        let (algorithm, hex_digest) = match s.split_once(':') {
+            Some((name, rest)) => (Self::from_name(name)?, rest),
+            None => (Algorithm::Turb1600, s),
+        };
+        Some((algorithm, hex::decode(hex_digest).ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_round_trip() {
+        for algorithm in [
+            Algorithm::Turb1600,
+            Algorithm::Turb256,
+            Algorithm::Turb512,
+            Algorithm::Tree(TREE_CHUNK_DEFAULT),
+            Algorithm::Tree(4 << 20),
+            Algorithm::Tree(1000),
+        ] {
+            let digest = algorithm.hash(b"abc");
+            assert_eq!(Algorithm::untag(&algorithm.tag(&digest)), Some((algorithm, digest)));
+        }
+        assert!(!Algorithm::Turb1600.tag(b"\x01").contains(':'));
+        assert_eq!(Algorithm::Tree(4 << 20).name(), "turb1600-tree-4MiB");
+        assert_eq!(Algorithm::untag("turb800:00"), None);
+        assert_eq!(Algorithm::untag("turb1600-tree-0:00"), None);
+        assert_eq!(Algorithm::from_name("turb1600-tree-1GiB"), Some(Algorithm::Tree(TREE_CHUNK_MAX)));
+        assert_eq!(Algorithm::untag("turb1600-tree-4096GiB:00"), None);
+    }
+}
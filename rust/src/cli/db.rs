@@ -0,0 +1,437 @@
+// =========================================================
+// turb1600 CLI — `db`: file-integrity database
+//
+//   turb1600 db init|update|verify [--fast|--paranoid] [--db <file>] <dir>
+//
+// Records digest, size and mtime for every regular file below
+// <dir> (symlinks are not followed) in a JSON database, by
+// default <dir>/.turb1600-db.json. The scan skips the database
+// and its temporary `.json.tmp` however their paths are spelled:
+//
+//   { "version": 1,
+//     "files": { "<relative/path>": { "digest": "<hex>",
+//                                     "size": <bytes>,
+//                                     "mtime_ns": <ns since epoch> } } }
+//
+// `init` creates it, `update` rewrites it after reporting
+// differences, `verify` only reports, exiting 1 on any:
+//
+//   ADDED  <path>  |  CHANGED  <path>  |  REMOVED  <path>
+//
+// Paths are keyed on the raw name bytes (`escape_path`). Files
+// that cannot be read are reported, skipped and not counted as
+// removed; `update` keeps their records and all modes exit 1.
+//
+// `--fast` trusts the recorded digest of any file whose size and
+// mtime are unchanged instead of re-reading it; content rewritten
+// with its mtime restored goes unnoticed. `--paranoid`, the
+// default, re-hashes everything. Manifests written with
+// `manifest --stat` get the same choice in `--check`.
+// =========================================================
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::escape_path;
+use super::manifest::Stat;
+
+pub const DEFAULT_DB_NAME: &str = ".turb1600-db.json";
+
+const DB_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct Record {
+    digest: String,
+    size: u64,
+    mtime_ns: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Database {
+    version: u32,
+    files: BTreeMap<String, Record>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mode {
+    Init,
+    Update,
+    Verify,
+}
+
+/// Where `store` writes before renaming over the database
+fn tmp_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("json.tmp")
+}
+
+/// Canonical form of a file path whose file may not exist yet
+fn canonical_file(path: &Path) -> io::Result<PathBuf> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("{}: not a file name", path.display())))?;
+    Ok(fs::canonicalize(parent)?.join(name))
+}
+
+/// A `walk` or `scan` result
+struct Scan {
+    records: BTreeMap<String, Record>,
+    /// Records reused from `trusted` without re-reading the file
+    skipped: usize,
+    /// Files and directories that could not be read; their errors
+    /// have already been printed
+    unreadable: Vec<String>,
+}
+
+impl Scan {
+    /// Whether `name` is, or lies below, something unreadable. The
+    /// root itself is `""`, which covers everything.
+    fn is_unreadable(&self, name: &str) -> bool {
+        self.unreadable.iter().any(|u| {
+            u.is_empty() || name == u || name.strip_prefix(u.as_str()).is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+/// Print an error for `path` and return `name` for `Scan::unreadable`
+fn unreadable(path: &Path, name: &str, e: io::Error) -> String {
+    eprintln!("{}: {}", path.display(), e);
+    name.to_string()
+}
+
+/// Names and paths of all regular files under `dir`, whose canonical
+/// path is `canonical`, except those in `skip`. A name is the
+/// [`escape_path`] form of each component joined with `/`, so two
+/// distinct paths never share one. Symlinks are not followed, so a
+/// child's canonical path is `canonical` joined with its name.
+///
+/// Only failing to list `dir` itself is an error; anything below it
+/// that cannot be read is reported and added to `failed`.
+fn walk(
+    dir: &Path,
+    canonical: &Path,
+    prefix: &str,
+    skip: &[PathBuf],
+    out: &mut Vec<(String, PathBuf)>,
+    failed: &mut Vec<String>,
+) -> io::Result<()> {
+    for item in fs::read_dir(dir)? {
+        let item = match item {
+            Ok(item) => item,
+            Err(e) => {
+                failed.push(unreadable(dir, prefix.strip_suffix('/').unwrap_or(prefix), e));
+                continue;
+            }
+        };
+        let path = item.path();
+        let name = format!("{}{}", prefix, escape_path(&item.file_name()));
+        let kind = match item.file_type() {
+            Ok(kind) => kind,
+            Err(e) => {
+                failed.push(unreadable(&path, &name, e));
+                continue;
+            }
+        };
+        let canonical = canonical.join(item.file_name());
+
+        if kind.is_dir() {
+            if let Err(e) = walk(&path, &canonical, &format!("{}/", name), skip, out, failed) {
+                failed.push(unreadable(&path, &name, e));
+            }
+        } else if kind.is_file() && !skip.contains(&canonical) {
+            out.push((name, path));
+        }
+    }
+    Ok(())
+}
+
+/// Hash every file under `dir`. Records in `trusted` whose size and
+/// mtime still match are reused as-is. Files that cannot be read are
+/// reported and skipped.
+fn scan(dir: &Path, db_path: &Path, trusted: Option<&BTreeMap<String, Record>>) -> io::Result<Scan> {
+    let skip = [canonical_file(db_path)?, canonical_file(&tmp_path(db_path))?];
+    let mut files = Vec::new();
+    let mut result = Scan {
+        records: BTreeMap::new(),
+        skipped: 0,
+        unreadable: Vec::new(),
+    };
+    walk(dir, &fs::canonicalize(dir)?, "", &skip, &mut files, &mut result.unreadable)?;
+
+    for (name, path) in files {
+        let current = match fs::metadata(&path) {
+            Ok(meta) => Stat::of(&meta),
+            Err(e) => {
+                result.unreadable.push(unreadable(&path, &name, e));
+                continue;
+            }
+        };
+        let Stat { size, mtime_ns } = current;
+
+        let known = trusted
+            .and_then(|t| t.get(&name))
+            .filter(|r| Stat { size: r.size, mtime_ns: r.mtime_ns }.unchanged(&current));
+        if let Some(record) = known {
+            result.records.insert(name, record.clone());
+            result.skipped += 1;
+            continue;
+        }
+
+        match turb1600::hash_file(&path) {
+            Ok(digest) => {
+                result.records.insert(
+                    name,
+                    Record {
+                        digest: hex::encode(digest),
+                        size,
+                        mtime_ns,
+                    },
+                );
+            }
+            Err(e) => result.unreadable.push(unreadable(&path, &name, e)),
+        }
+    }
+    Ok(result)
+}
+
+fn load(db_path: &Path) -> Result<Database, String> {
+    let text = fs::read_to_string(db_path).map_err(|e| format!("{}: {}", db_path.display(), e))?;
+    let db: Database =
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", db_path.display(), e))?;
+    if db.version != DB_VERSION {
+        return Err(format!("{}: unsupported version {}", db_path.display(), db.version));
+    }
+    Ok(db)
+}
+
+fn store(db_path: &Path, files: BTreeMap<String, Record>) -> Result<(), String> {
+    let db = Database {
+        version: DB_VERSION,
+        files,
+    };
+    let mut text = serde_json::to_string_pretty(&db).map_err(|e| e.to_string())?;
+    text.push('\n');
+
+    // Write beside the target and rename, so a crash never leaves a torn database
+    let tmp = tmp_path(db_path);
+    fs::write(&tmp, text)
+        .and_then(|()| fs::rename(&tmp, db_path))
+        .map_err(|e| format!("{}: {}", db_path.display(), e))
+}
+
+/// Differences as (`ADDED` | `CHANGED` | `REMOVED`, path), in that
+/// order. Recorded files that could not be read now are not removed.
+fn changes<'a>(old: &'a BTreeMap<String, Record>, new: &'a Scan) -> Vec<(&'static str, &'a str)> {
+    let mut out = Vec::new();
+    for (name, record) in &new.records {
+        match old.get(name) {
+            None => out.push(("ADDED", name.as_str())),
+            Some(prev) if prev.digest != record.digest => out.push(("CHANGED", name.as_str())),
+            Some(_) => {}
+        }
+    }
+    for name in old.keys() {
+        if !new.records.contains_key(name) && !new.is_unreadable(name) {
+            out.push(("REMOVED", name.as_str()));
+        }
+    }
+    out
+}
+
+/// Print differences; returns how many there were, counting
+/// unreadable files.
+fn report(old: &BTreeMap<String, Record>, new: &Scan) -> usize {
+    let (mut added, mut changed, mut removed) = (0, 0, 0);
+    for (kind, name) in changes(old, new) {
+        println!("{}  {}", kind, name);
+        match kind {
+            "ADDED" => added += 1,
+            "CHANGED" => changed += 1,
+            _ => removed += 1,
+        }
+    }
+
+    print!(
+        "{} files: {} added, {} changed, {} removed",
+        new.records.len(),
+        added,
+        changed,
+        removed
+    );
+    if !new.unreadable.is_empty() {
+        print!(", {} unreadable", new.unreadable.len());
+    }
+    if new.skipped > 0 {
+        print!(" ({} unchanged by size and mtime, not re-hashed)", new.skipped);
+    }
+    println!();
+    added + changed + removed + new.unreadable.len()
+}
+
+/// Run a `db` command; returns the process exit code.
+pub fn run(mode: Mode, dir: &str, db_path: Option<&str>, fast: bool) -> Result<i32, String> {
+    let dir = Path::new(dir);
+    let db_path = match db_path {
+        Some(p) => PathBuf::from(p),
+        None => dir.join(DEFAULT_DB_NAME),
+    };
+
+    if mode == Mode::Init && db_path.exists() {
+        return Err(format!("{}: already exists (use `db update`)", db_path.display()));
+    }
+    let old = match mode {
+        Mode::Init => BTreeMap::new(),
+        Mode::Update | Mode::Verify => load(&db_path)?.files,
+    };
+
+    let trusted = if fast { Some(&old) } else { None };
+    let mut new = scan(dir, &db_path, trusted).map_err(|e| e.to_string())?;
+    let failed = if new.unreadable.is_empty() { 0 } else { 1 };
+
+    match mode {
+        Mode::Init => {
+            println!("{} files recorded", new.records.len());
+            store(&db_path, new.records)?;
+            Ok(failed)
+        }
+        Mode::Update => {
+            report(&old, &new);
+            // Keep what could not be re-read as it was
+            for (name, record) in &old {
+                if !new.records.contains_key(name) && new.is_unreadable(name) {
+                    new.records.insert(name.clone(), record.clone());
+                }
+            }
+            store(&db_path, new.records)?;
+            Ok(failed)
+        }
+        Mode::Verify => Ok(if report(&old, &new) > 0 { 1 } else { 0 }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    fn temp_dir(name: &str) -> TestDir {
+        let dir = TestDir::new(name);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_changes_added_changed_removed() {
+        let dir = temp_dir("changes");
+        fs::write(dir.join("keep"), b"same").unwrap();
+        fs::write(dir.join("edit"), b"before").unwrap();
+        fs::write(dir.join("sub/gone"), b"bye").unwrap();
+        let db_path = dir.join(DEFAULT_DB_NAME);
+        let old = scan(&dir, &db_path, None).unwrap().records;
+
+        fs::write(dir.join("edit"), b"after").unwrap();
+        fs::remove_file(dir.join("sub/gone")).unwrap();
+        fs::write(dir.join("sub/new"), b"hi").unwrap();
+        let new = scan(&dir, &db_path, None).unwrap();
+
+        assert_eq!(
+            changes(&old, &new),
+            [("CHANGED", "edit"), ("ADDED", "sub/new"), ("REMOVED", "sub/gone")]
+        );
+        assert!(changes(&new.records, &new).is_empty());
+    }
+
+    #[test]
+    fn test_scan_skips_database_and_temp_file() {
+        let dir = temp_dir("skip");
+        fs::write(dir.join("data"), b"payload").unwrap();
+        fs::write(dir.join("sums.json"), b"{}").unwrap();
+        fs::write(dir.join("sums.json.tmp"), b"{").unwrap();
+
+        // Spelled differently from the paths the walk produces
+        let db_path = dir.join("sub/../sums.json");
+        let records = scan(&dir, &db_path, None).unwrap().records;
+        let default = scan(&dir, &dir.join(DEFAULT_DB_NAME), None).unwrap().records;
+
+        assert_eq!(records.keys().collect::<Vec<_>>(), ["data"]);
+        assert_eq!(default.len(), 3);
+    }
+
+    #[test]
+    fn test_fast_reuses_records_until_size_changes() {
+        let dir = temp_dir("fast");
+        fs::write(dir.join("a"), b"first").unwrap();
+        fs::write(dir.join("b"), b"second").unwrap();
+        let db_path = dir.join(DEFAULT_DB_NAME);
+        let mut old = scan(&dir, &db_path, None).unwrap().records;
+
+        // A recorded digest that no longer matches is trusted while
+        // size and mtime match, which proves the file was not re-read
+        old.get_mut("a").unwrap().digest = "stale".to_string();
+        let fast = scan(&dir, &db_path, Some(&old)).unwrap();
+        assert_eq!(fast.skipped, 2);
+        assert_eq!(fast.records["a"].digest, "stale");
+        let paranoid = scan(&dir, &db_path, None).unwrap();
+        assert_eq!(paranoid.skipped, 0);
+        assert_ne!(paranoid.records["a"].digest, "stale");
+
+        old.get_mut("a").unwrap().size += 1;
+        let fast = scan(&dir, &db_path, Some(&old)).unwrap();
+        assert_eq!(fast.skipped, 1);
+        assert_eq!(fast.records["a"].digest, paranoid.records["a"].digest);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_names_get_distinct_keys() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = temp_dir("bytes");
+        fs::write(dir.join(OsStr::from_bytes(b"caf\xe9")), b"latin-1").unwrap();
+        fs::write(dir.join(OsStr::from_bytes(b"caf\xff")), b"other").unwrap();
+        fs::write(dir.join("a\\b"), b"backslash").unwrap();
+        let records = scan(&dir, &dir.join(DEFAULT_DB_NAME), None).unwrap().records;
+
+        assert_eq!(
+            records.keys().collect::<Vec<_>>(),
+            ["a\\\\b", "caf\\xe9", "caf\\xff"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_files_are_skipped_not_removed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("unreadable");
+        fs::write(dir.join("ok"), b"fine").unwrap();
+        fs::write(dir.join("sub/locked"), b"secret").unwrap();
+        let db_path = dir.join(DEFAULT_DB_NAME);
+        let old = scan(&dir, &db_path, None).unwrap().records;
+
+        fs::set_permissions(dir.join("sub"), fs::Permissions::from_mode(0o000)).unwrap();
+        let locked = fs::read_dir(dir.join("sub")).is_err();
+        let new = scan(&dir, &db_path, None).unwrap();
+        fs::set_permissions(dir.join("sub"), fs::Permissions::from_mode(0o755)).unwrap();
+        if locked {
+            assert_eq!(new.unreadable, ["sub"]);
+            assert_eq!(new.records.keys().collect::<Vec<_>>(), ["ok"]);
+            assert!(changes(&old, &new).is_empty());
+        }
+
+        // Root ignores permissions, so check the bookkeeping directly too
+        let mut new = scan(&dir, &db_path, None).unwrap();
+        new.records.remove("sub/locked");
+        new.unreadable.push("sub".to_string());
+        assert!(changes(&old, &new).is_empty());
+        assert!(!new.is_unreadable("subway") && !new.is_unreadable("ok"));
+    }
+}
@@ -0,0 +1,284 @@
+// =========================================================
+// turb1600 CLI — `dupes`: find duplicate files
+//
+//   turb1600 dupes [--delete | --hardlink] [--yes] <dir>...
+//
+// Regular files below the given directories are grouped by size,
+// then by a digest of their first PARTIAL_BYTES, and only the
+// survivors are hashed in full. Each group of identical files is
+// printed as manifest lines, `<hex>  <path>`, with a blank line
+// between groups. Empty files and symlinks are skipped, and on
+// Unix several hard links to one file count as a single file,
+// listed under the first name found. A file reached twice through
+// repeated or nested directory arguments is only counted once.
+//
+// `--delete` removes every copy but the first path of each group
+// (in byte order); `--hardlink` replaces them with hard links to
+// it. Either applies to all names of a removed copy. Both ask for
+// confirmation on stdin unless `--yes` is given.
+// =========================================================
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::algorithm::Algorithm;
+use super::manifest;
+
+/// Prefix hashed to split same-size candidates cheaply.
+const PARTIAL_BYTES: u64 = 64 << 10;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Report,
+    Delete,
+    Hardlink,
+}
+
+/// Identity of the underlying file, so hard links are not reported
+#[cfg(unix)]
+fn file_id(meta: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_meta: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[derive(Default)]
+struct Scan {
+    /// Candidate files by size, one path per underlying file
+    by_size: BTreeMap<u64, Vec<PathBuf>>,
+    /// First path seen for each file identity
+    first: HashMap<(u64, u64), PathBuf>,
+    /// Further hard links, keyed by the first path
+    aliases: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Canonical paths of the directories and files already walked
+    seen: HashSet<PathBuf>,
+}
+
+impl Scan {
+    /// Walk `root`, skipping anything an earlier root already covered
+    fn collect(&mut self, root: &Path) -> io::Result<()> {
+        let canonical = fs::canonicalize(root)?;
+        if self.seen.insert(canonical.clone()) {
+            self.collect_dir(root, &canonical)?;
+        }
+        Ok(())
+    }
+
+    /// Symlinks are not followed, so `canonical` joined with an entry's
+    /// name is that entry's canonical path
+    fn collect_dir(&mut self, dir: &Path, canonical: &Path) -> io::Result<()> {
+        for item in fs::read_dir(dir)? {
+            let item = item?;
+            let path = item.path();
+            let canonical = canonical.join(item.file_name());
+            if !self.seen.insert(canonical.clone()) {
+                continue;
+            }
+            let meta = fs::symlink_metadata(&path)?;
+
+            if meta.is_dir() {
+                self.collect_dir(&path, &canonical)?;
+            } else if meta.is_file() && meta.len() > 0 {
+                if let Some(id) = file_id(&meta) {
+                    if let Some(first) = self.first.get(&id) {
+                        self.aliases.entry(first.clone()).or_default().push(path);
+                        continue;
+                    }
+                    self.first.insert(id, path.clone());
+                }
+                self.by_size.entry(meta.len()).or_default().push(path);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn hash_prefix(path: &Path) -> io::Result<Vec<u8>> {
+    turb1600::hash_reader(File::open(path)?.take(PARTIAL_BYTES), PARTIAL_BYTES as usize)
+}
+
+/// Split `paths` by `hash`, keeping only groups with two or more members
+fn refine<F>(paths: Vec<PathBuf>, hash: F) -> Vec<(Vec<u8>, Vec<PathBuf>)>
+where
+    F: Fn(&Path) -> io::Result<Vec<u8>>,
+{
+    let mut groups: HashMap<Vec<u8>, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        match hash(&path) {
+            Ok(digest) => groups.entry(digest).or_default().push(path),
+            Err(e) => eprintln!("{}: {}", path.display(), e),
+        }
+    }
+    groups.into_iter().filter(|(_, paths)| paths.len() > 1).collect()
+}
+
+/// Replace `dup` with a hard link to `keep`, via a temporary name so
+/// `dup` is never missing
+fn hardlink(keep: &Path, dup: &Path) -> io::Result<()> {
+    let mut tmp = dup.as_os_str().to_owned();
+    tmp.push(".turb1600-link");
+    let tmp = PathBuf::from(tmp);
+
+    fs::hard_link(keep, &tmp)?;
+    if let Err(e) = fs::rename(&tmp, dup) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Whether `a` and `b` name the same file, however they were reached
+fn same_file(a: &Path, b: &Path) -> io::Result<bool> {
+    if fs::canonicalize(a)? == fs::canonicalize(b)? {
+        return Ok(true);
+    }
+    let (a, b) = (file_id(&fs::metadata(a)?), file_id(&fs::metadata(b)?));
+    Ok(a.is_some() && a == b)
+}
+
+fn confirm(prompt: &str) -> bool {
+    eprint!("{} [y/N] ", prompt);
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).is_ok()
+        && matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+/// Returns the exit code: 0 when no action failed.
+pub fn run(dirs: &[String], action: Action, assume_yes: bool) -> Result<i32, String> {
+    let mut scan = Scan::default();
+    for dir in dirs {
+        scan.collect(Path::new(dir)).map_err(|e| format!("{}: {}", dir, e))?;
+    }
+
+    let mut groups: Vec<(u64, Vec<u8>, Vec<PathBuf>)> = Vec::new();
+    for (size, paths) in std::mem::take(&mut scan.by_size).into_iter().filter(|(_, paths)| paths.len() > 1) {
+        for (prefix, paths) in refine(paths, hash_prefix) {
+            if size <= PARTIAL_BYTES {
+                groups.push((size, prefix, paths));
+                continue;
+            }
+            for (digest, paths) in refine(paths, |path| turb1600::hash_file(path)) {
+                groups.push((size, digest, paths));
+            }
+        }
+    }
+    for (_, _, paths) in &mut groups {
+        paths.sort();
+    }
+    groups.sort_by(|a, b| a.2[0].cmp(&b.2[0]));
+
+    let (mut copies, mut reclaimable) = (0usize, 0u64);
+    for (i, (size, digest, paths)) in groups.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        for path in paths {
+            println!("{}", manifest::line(Algorithm::Turb1600, digest, path));
+        }
+        copies += paths.len() - 1;
+        reclaimable += size * (paths.len() as u64 - 1);
+    }
+    eprintln!(
+        "{} duplicate file(s) in {} group(s), {} bytes reclaimable",
+        copies,
+        groups.len(),
+        reclaimable
+    );
+
+    let verb = match action {
+        Action::Report => return Ok(0),
+        _ if copies == 0 => return Ok(0),
+        Action::Delete => "Delete",
+        Action::Hardlink => "Hard-link",
+    };
+    if !assume_yes && !confirm(&format!("{} {} duplicate file(s)?", verb, copies)) {
+        eprintln!("turb1600: dupes: nothing changed");
+        return Ok(1);
+    }
+
+    let mut failures = 0;
+    for (_, _, paths) in &groups {
+        let (keep, dups) = paths.split_first().expect("groups have two or more paths");
+        let names = dups
+            .iter()
+            .flat_map(|dup| std::iter::once(dup).chain(scan.aliases.get(dup).into_iter().flatten()));
+        for name in names {
+            // Deleting or replacing the kept file itself would lose the data
+            match same_file(keep, name) {
+                Ok(false) => {}
+                Ok(true) => {
+                    eprintln!("{}: same file as {}, skipped", name.display(), keep.display());
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("{}: {}", name.display(), e);
+                    failures += 1;
+                    continue;
+                }
+            }
+            let result = match action {
+                Action::Delete => fs::remove_file(name),
+                _ => hardlink(keep, name),
+            };
+            if let Err(e) = result {
+                eprintln!("{}: {}", name.display(), e);
+                failures += 1;
+            }
+        }
+    }
+
+    Ok(if failures > 0 { 1 } else { 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn test_refine_keeps_only_shared_digests() {
+        let root = TestDir::new("dupes");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        let big = vec![7u8; PARTIAL_BYTES as usize + 10];
+        let mut other = big.clone();
+        *other.last_mut().unwrap() = 8;
+        fs::write(root.join("a"), &big).unwrap();
+        fs::write(root.join("sub/b"), &big).unwrap();
+        fs::write(root.join("c"), &other).unwrap();
+
+        let mut scan = Scan::default();
+        scan.collect(&root).unwrap();
+        let candidates = scan.by_size.remove(&(big.len() as u64)).unwrap();
+        assert_eq!(refine(candidates.clone(), hash_prefix).len(), 1);
+
+        let full = refine(candidates, |path| turb1600::hash_file(path));
+        assert_eq!(full.len(), 1);
+        assert_eq!(full[0].1.len(), 2);
+    }
+
+    #[test]
+    fn test_repeated_and_nested_roots_count_once() {
+        let root = TestDir::new("dupes-roots");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("sub/only"), b"one copy").unwrap();
+
+        let mut scan = Scan::default();
+        for dir in [root.to_path_buf(), root.join("sub"), root.join("sub/../sub"), root.to_path_buf()] {
+            scan.collect(&dir).unwrap();
+        }
+        let found: Vec<_> = scan.by_size.values().flatten().collect();
+        assert_eq!(found.len(), 1);
+
+        let only = root.join("sub/only");
+        assert!(same_file(&only, &root.join("sub/../sub/only")).unwrap());
+        fs::write(root.join("copy"), b"one copy").unwrap();
+        assert!(!same_file(&only, &root.join("copy")).unwrap());
+    }
+}
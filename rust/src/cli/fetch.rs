@@ -0,0 +1,17 @@
+// =========================================================
+// turb1600 CLI — `--url`: hash a remote resource
+//
+// The response body is streamed through the hasher without a
+// temporary file. Compression is not negotiated, so the digest
+// covers exactly the bytes the server publishes.
+// =========================================================
+
+use turb1600::Turb1600;
+
+/// Digest of the response body, and its length in bytes
+pub fn hash_url(url: &str) -> Result<(Vec<u8>, u64), String> {
+    let response = ureq::get(url).call().map_err(|e| e.to_string())?;
+    let mut hasher = Turb1600::new();
+    let len = hasher.update_reader(response.into_reader()).map_err(|e| e.to_string())?;
+    Ok((hasher.finalize(), len))
+}
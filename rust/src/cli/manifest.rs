@@ -0,0 +1,476 @@
+// =========================================================
+// turb1600 CLI — digest manifests
+//
+//   <hex digest>  <path>
+//   <algorithm>:<hex digest>  <path>
+//   <hex digest> *<path>
+//
+// One entry per line in the layout of `sha256sum`; blank lines
+// and lines starting with `#` are ignored. Untagged digests are
+// turb1600; others name their algorithm (see `algorithm.rs`).
+// As in coreutils, the character before the path marks text (` `)
+// or binary (`*`) mode. turb1600 always hashes the bytes as they
+// are, so the marker only records how the line was written and is
+// kept when files interchange with `sha*sum`-style tools.
+//
+// Also as in coreutils, a path holding a backslash, newline or
+// carriage return starts its line with `\` and is written with
+// `\\`, `\n` and `\r` (see `escape_path`); `--check` prints it the
+// same way. Bytes that are not UTF-8 become `\xNN`, which coreutils
+// would write raw.
+//
+// `manifest --stat` puts a line before each entry recording the
+// file's size and modification time,
+//
+//   # turb1600-stat: <size> <mtime ns since epoch>
+//
+// which `--check --fast` uses to skip files whose size and mtime
+// are unchanged, trusting their recorded digest. Other tools read
+// it as a comment.
+//
+// A keyed manifest ends with
+//
+//   # turb1600-mac: <hex>
+//
+// the turb1600 MAC over MAC_DOMAIN followed by every entry
+// re-serialized as its stat line, if any, and `Entry::line()`,
+// each ending in "\n", in file order. Comments and
+// blank lines are not covered, so only entries are protected, but
+// no entry can be edited, added, removed or reordered unnoticed.
+// =========================================================
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use turb1600::key::SecretKey;
+use turb1600::{turb1600_mac, turb1600_mac_verify};
+
+use super::algorithm::Algorithm;
+use super::{escape_path, unescape_path};
+
+const MAC_PREFIX: &str = "# turb1600-mac: ";
+const STAT_PREFIX: &str = "# turb1600-stat: ";
+const MAC_DOMAIN: &[u8] = b"turb1600 manifest v1\n";
+
+pub struct Entry {
+    pub algorithm: Algorithm,
+    pub digest: Vec<u8>,
+    /// `*` marker rather than a space before the path
+    pub binary: bool,
+    pub path: PathBuf,
+    /// Size and mtime recorded by `manifest --stat`
+    pub stat: Option<Stat>,
+}
+
+/// A file's size and modification time, as `--fast` compares them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stat {
+    pub size: u64,
+    /// Nanoseconds since the Unix epoch; 0 when unavailable
+    pub mtime_ns: i64,
+}
+
+impl Stat {
+    pub fn of(meta: &fs::Metadata) -> Self {
+        let mtime_ns = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos() as i64);
+        Stat { size: meta.len(), mtime_ns }
+    }
+
+    /// Whether a file with `self` recorded may be trusted unread now
+    /// that it has `current`. An unknown mtime never matches.
+    pub fn unchanged(&self, current: &Stat) -> bool {
+        self == current && self.mtime_ns != 0
+    }
+}
+
+pub struct Manifest {
+    pub entries: Vec<Entry>,
+    /// Trailing MAC line, if the manifest is keyed
+    pub mac: Option<Vec<u8>>,
+}
+
+/// `path` as manifest lines show it, after the `\` flag that
+/// marks it escaped, if any.
+fn quote(path: &Path) -> (&'static str, String) {
+    let escaped = escape_path(path.as_os_str());
+    match path.to_str() {
+        Some(plain) if plain == escaped => ("", escaped),
+        _ => ("\\", escaped),
+    }
+}
+
+/// `path` as `--check` prints it, flag included.
+pub fn shown(path: &Path) -> String {
+    let (flag, name) = quote(path);
+    format!("{}{}", flag, name)
+}
+
+fn format_line(algorithm: Algorithm, digest: &[u8], binary: bool, path: &Path) -> String {
+    let (flag, name) = quote(path);
+    let marker = if binary { '*' } else { ' ' };
+    format!("{}{} {}{}", flag, algorithm.tag(digest), marker, name)
+}
+
+/// Format one text-mode manifest line (without the newline).
+pub fn line(algorithm: Algorithm, digest: &[u8], path: &Path) -> String {
+    format_line(algorithm, digest, false, path)
+}
+
+impl Entry {
+    /// The entry's line, with its mode marker (without the newline).
+    pub fn line(&self) -> String {
+        format_line(self.algorithm, &self.digest, self.binary, &self.path)
+    }
+
+    /// Whether the file still has the recorded size and mtime.
+    fn is_unchanged(&self) -> bool {
+        let current = fs::metadata(&self.path).map(|meta| Stat::of(&meta));
+        matches!((self.stat, current), (Some(recorded), Ok(current)) if recorded.unchanged(&current))
+    }
+
+    /// The `# turb1600-stat:` line, if a stat was recorded.
+    pub fn stat_line(&self) -> Option<String> {
+        self.stat
+            .map(|stat| format!("{}{} {}", STAT_PREFIX, stat.size, stat.mtime_ns))
+    }
+}
+
+fn canonical(entries: &[Entry]) -> Vec<u8> {
+    let mut out = MAC_DOMAIN.to_vec();
+    for entry in entries {
+        for line in entry.stat_line().into_iter().chain([entry.line()]) {
+            out.extend_from_slice(line.as_bytes());
+            out.push(b'\n');
+        }
+    }
+    out
+}
+
+/// The `# turb1600-mac:` line authenticating `entries` under `key`.
+pub fn mac_line(key: &SecretKey, entries: &[Entry]) -> String {
+    format!("{}{}", MAC_PREFIX, hex::encode(turb1600_mac(key, &canonical(entries))))
+}
+
+pub fn parse(text: &str) -> Result<Manifest, String> {
+    let mut entries = Vec::new();
+    let mut mac = None;
+    let mut stat = None;
+
+    for (n, raw) in text.lines().enumerate() {
+        if let Some(fields) = raw.strip_prefix(STAT_PREFIX) {
+            let parsed = fields
+                .split_once(' ')
+                .and_then(|(size, mtime)| Some(Stat { size: size.parse().ok()?, mtime_ns: mtime.parse().ok()? }));
+            match parsed {
+                Some(parsed) if stat.is_none() => stat = Some(parsed),
+                _ => return Err(format!("line {}: bad stat line", n + 1)),
+            }
+            continue;
+        }
+        if stat.is_some() {
+            // The stat line belongs to the entry right after it
+            if raw.starts_with('#') || raw.trim().is_empty() {
+                return Err(format!("line {}: stat line not followed by an entry", n + 1));
+            }
+        }
+        if let Some(tag) = raw.strip_prefix(MAC_PREFIX) {
+            match hex::decode(tag.trim()) {
+                Ok(tag) if mac.is_none() => mac = Some(tag),
+                _ => return Err(format!("line {}: bad or repeated MAC line", n + 1)),
+            }
+            continue;
+        }
+        if raw.trim().is_empty() || raw.starts_with('#') {
+            continue;
+        }
+
+        let (escaped, raw) = match raw.strip_prefix('\\') {
+            Some(rest) => (true, rest),
+            None => (false, raw),
+        };
+        let parsed = raw.split_once(' ').and_then(|(digest, rest)| {
+            let binary = match rest.as_bytes().first()? {
+                b' ' => false,
+                b'*' => true,
+                _ => return None,
+            };
+            Some((Algorithm::untag(digest)?, binary, &rest[1..]))
+        });
+
+        match parsed {
+            Some(((algorithm, digest), binary, path)) if !path.is_empty() => {
+                let path = match escaped {
+                    true => unescape_path(path).ok_or_else(|| format!("line {}: bad escape in path", n + 1))?,
+                    false => PathBuf::from(path),
+                };
+                entries.push(Entry {
+                    algorithm,
+                    digest,
+                    binary,
+                    path,
+                    stat: stat.take(),
+                });
+            }
+            _ => return Err(format!("line {}: expected `[<algorithm>:]<hex>  <path>` or `... *<path>`", n + 1)),
+        }
+    }
+
+    if stat.is_some() {
+        return Err("stat line at end of manifest".to_string());
+    }
+    Ok(Manifest { entries, mac })
+}
+
+/// Read and parse a manifest file.
+pub fn load(path: &str) -> Result<Manifest, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    parse(&text).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Read a MAC key: the raw bytes of `path`.
+pub fn load_key(path: &str) -> Result<SecretKey, String> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    if bytes.is_empty() {
+        return Err(format!("{}: key file is empty", path));
+    }
+    Ok(SecretKey::new(&bytes))
+}
+
+/// Options for `manifest`
+#[derive(Clone, Copy)]
+pub struct Options {
+    pub algorithm: Algorithm,
+    /// `*` marker rather than a space before the path
+    pub binary: bool,
+    /// Record size and mtime for `--check --fast`
+    pub stat: bool,
+}
+
+fn add_entries(options: Options, path: &Path, entries: &mut Vec<Entry>) -> Result<(), String> {
+    let fail = |e: std::io::Error| format!("{}: {}", path.display(), e);
+
+    if fs::symlink_metadata(path).map_err(fail)?.is_dir() {
+        let mut items = fs::read_dir(path)
+            .and_then(|items| items.map(|item| item.map(|i| i.path())).collect::<Result<Vec<_>, _>>())
+            .map_err(fail)?;
+        items.sort();
+        for item in items {
+            add_entries(options, &item, entries)?;
+        }
+        return Ok(());
+    }
+
+    // Stat before hashing: a file written meanwhile then fails the
+    // fast comparison and is re-hashed rather than trusted
+    let stat = match options.stat {
+        true => Some(Stat::of(&fs::metadata(path).map_err(fail)?)),
+        false => None,
+    };
+    entries.push(Entry {
+        algorithm: options.algorithm,
+        digest: options.algorithm.hash_file(path).map_err(fail)?,
+        binary: options.binary,
+        path: path.to_path_buf(),
+        stat,
+    });
+    Ok(())
+}
+
+/// `manifest`: print an entry for every file under `paths`
+/// (directories recursively, in byte order), then the MAC line if
+/// a key is given.
+pub fn create(options: Options, key: Option<&SecretKey>, paths: &[String]) -> Result<(), String> {
+    let mut entries = Vec::new();
+    for path in paths {
+        add_entries(options, Path::new(path), &mut entries)?;
+    }
+
+    for entry in &entries {
+        for line in entry.stat_line().into_iter().chain([entry.line()]) {
+            println!("{}", line);
+        }
+    }
+    if let Some(key) = key {
+        println!("{}", mac_line(key, &entries));
+    }
+    Ok(())
+}
+
+/// `--check <manifest>`: re-hash every entry with its own algorithm
+/// and print `<path>: OK` or `<path>: FAILED`. Returns the exit code.
+///
+/// With a key, the manifest's MAC must verify before any line is
+/// trusted; a missing or wrong MAC fails the whole check. With
+/// `fast`, entries whose recorded stat still matches are reported
+/// OK without re-hashing.
+pub fn check(manifest_path: &str, key: Option<&SecretKey>, fast: bool) -> Result<i32, String> {
+    let manifest = load(manifest_path)?;
+    if let Some(key) = key {
+        let authentic = manifest
+            .mac
+            .as_ref()
+            .is_some_and(|tag| turb1600_mac_verify(key, &canonical(&manifest.entries), tag));
+        if !authentic {
+            return Err(format!(
+                "{}: MAC {}",
+                manifest_path,
+                if manifest.mac.is_some() { "does not verify" } else { "missing" }
+            ));
+        }
+    }
+
+    let (mut failures, mut skipped) = (0, 0);
+    for entry in manifest.entries {
+        let name = shown(&entry.path);
+        if fast && entry.is_unchanged() {
+            println!("{}: OK", name);
+            skipped += 1;
+            continue;
+        }
+        match entry.algorithm.hash_file(&entry.path) {
+            Ok(digest) if digest == entry.digest => println!("{}: OK", name),
+            Ok(_) => {
+                println!("{}: FAILED", name);
+                failures += 1;
+            }
+            Err(e) => {
+                println!("{}: FAILED ({})", name, e);
+                failures += 1;
+            }
+        }
+    }
+
+    if skipped > 0 {
+        eprintln!("{} file(s) unchanged by size and mtime, not re-hashed", skipped);
+    }
+    Ok(if failures > 0 { 1 } else { 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn test_parse_mixed_algorithms() {
+        let text = format!(
+            "# sums\n{}\n{}\n",
+            line(Algorithm::Turb1600, &[0xab; 4], Path::new("a.bin")),
+            line(Algorithm::Tree(1 << 20), &[0xcd; 4], Path::new("dir/b  c.bin")),
+        );
+        let entries = parse(&text).unwrap().entries;
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].algorithm, entries[0].path.as_path()), (Algorithm::Turb1600, Path::new("a.bin")));
+        assert_eq!(
+            (entries[1].algorithm, entries[1].path.as_path()),
+            (Algorithm::Tree(1 << 20), Path::new("dir/b  c.bin"))
+        );
+        assert_eq!(entries[1].digest, [0xcd; 4]);
+        assert!(parse("turb800:abcd  x\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_mode_markers() {
+        let entries = parse("abcd *a.bin\nabcd  b.txt\nturb1600-256:abcd * c\n").unwrap().entries;
+        let modes: Vec<_> = entries.iter().map(|e| (e.binary, e.path.to_str().unwrap())).collect();
+        assert_eq!(modes, [(true, "a.bin"), (false, "b.txt"), (true, " c")]);
+        assert_eq!(entries[0].line(), "abcd *a.bin");
+        assert!(parse("abcd x\n").is_err());
+        assert!(parse("abcd *\n").is_err());
+    }
+
+    #[test]
+    fn test_paths_are_escaped_like_coreutils() {
+        let odd = Path::new("new\nline\\back");
+        let text = line(Algorithm::Turb1600, &[0xab; 2], odd);
+        assert_eq!(text, "\\abab  new\\nline\\\\back");
+        assert_eq!(line(Algorithm::Turb1600, &[0xab; 2], Path::new("a b")), "abab  a b");
+
+        let entries = parse(&format!("{}\n", text)).unwrap().entries;
+        assert_eq!(entries[0].path, odd);
+        assert_eq!(entries[0].line(), text);
+        assert_eq!(shown(odd), "\\new\\nline\\\\back");
+        // A plain line keeps its backslashes literally
+        assert_eq!(parse("abab  a\\n\n").unwrap().entries[0].path, Path::new("a\\n"));
+        assert!(parse("\\abab  a\\q\n").is_err());
+
+        #[cfg(unix)]
+        {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt;
+            let raw = Path::new(OsStr::from_bytes(b"caf\xe9"));
+            let text = format!("{}\n", line(Algorithm::Turb1600, &[0xab; 2], raw));
+            assert_eq!(text, "\\abab  caf\\xe9\n");
+            assert_eq!(parse(&text).unwrap().entries[0].path, raw);
+        }
+    }
+
+    #[test]
+    fn test_mac_covers_entries() {
+        let key = SecretKey::new(b"manifest key");
+        let text = format!(
+            "{}\n{}\n",
+            line(Algorithm::Turb1600, &[1; 4], Path::new("a")),
+            line(Algorithm::Tree(1 << 20), &[2; 4], Path::new("b"))
+        );
+        let signed = format!("{}# comment\n{}\n", text, mac_line(&key, &parse(&text).unwrap().entries));
+
+        let manifest = parse(&signed).unwrap();
+        let tag = manifest.mac.unwrap();
+        assert!(turb1600_mac_verify(&key, &canonical(&manifest.entries), &tag));
+
+        let swapped = signed.replace("01010101", "01010102");
+        assert!(!turb1600_mac_verify(&key, &canonical(&parse(&swapped).unwrap().entries), &tag));
+        assert!(!turb1600_mac_verify(&SecretKey::new(b"other"), &canonical(&manifest.entries), &tag));
+    }
+
+    #[test]
+    fn test_stat_lines_attach_to_the_next_entry() {
+        let text = "# turb1600-stat: 5 1700000000000000000\nabcd  a\nabcd  b\n";
+        let entries = parse(text).unwrap().entries;
+        assert_eq!(entries[0].stat, Some(Stat { size: 5, mtime_ns: 1_700_000_000_000_000_000 }));
+        assert_eq!(entries[0].stat_line().unwrap(), text.lines().next().unwrap());
+        assert_eq!(entries[1].stat, None);
+
+        for bad in [
+            "# turb1600-stat: 5\nabcd  a\n",
+            "# turb1600-stat: 5 x\nabcd  a\n",
+            "# turb1600-stat: 5 1\n# turb1600-stat: 5 1\nabcd  a\n",
+            "# turb1600-stat: 5 1\n# comment\nabcd  a\n",
+            "abcd  a\n# turb1600-stat: 5 1\n",
+        ] {
+            assert!(parse(bad).is_err(), "{:?}", bad);
+        }
+
+        // Under a key, the stat is as protected as the digest
+        let key = SecretKey::new(b"k");
+        let tag = hex::decode(&mac_line(&key, &entries)[MAC_PREFIX.len()..]).unwrap();
+        let moved = parse(&text.replace(" 5 ", " 6 ")).unwrap().entries;
+        assert!(!turb1600_mac_verify(&key, &canonical(&moved), &tag));
+    }
+
+    #[test]
+    fn test_fast_trusts_only_unchanged_stats() {
+        let dir = TestDir::new("manifest-stat");
+        let path = dir.join("file");
+        fs::write(&path, b"contents").unwrap();
+        let options = Options { algorithm: Algorithm::Turb1600, binary: false, stat: true };
+
+        let mut entries = Vec::new();
+        add_entries(options, &path, &mut entries).unwrap();
+        add_entries(Options { stat: false, ..options }, &path, &mut entries).unwrap();
+        assert_eq!(entries[0].stat.unwrap().size, 8);
+        assert!(entries[0].is_unchanged());
+        assert!(!entries[1].is_unchanged());
+
+        fs::write(&path, b"longer contents").unwrap();
+        assert!(!entries[0].is_unchanged());
+        let unknown = Stat { size: 15, mtime_ns: 0 };
+        assert!(!unknown.unchanged(&unknown));
+    }
+}
@@ -0,0 +1,152 @@
+// =========================================================
+// turb1600 CLI — subcommands and shared helpers
+// =========================================================
+
+pub mod algorithm;
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "db")]
+pub mod db;
+pub mod dupes;
+#[cfg(feature = "net")]
+pub mod fetch;
+pub mod manifest;
+pub mod pieces;
+pub mod randomart;
+pub mod serve;
+pub mod tee;
+#[cfg(feature = "watch")]
+pub mod watch;
+
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+/// Parse a byte count such as `4096`, `64K`, `4MiB` or `1GB`.
+///
+/// `K`/`M`/`G`/`T` and the `iB` forms are powers of 1024; the `B`
+/// forms (`KB`, `MB`, …) are powers of 1000, as in `dd`.
+pub fn parse_size(s: &str) -> Option<u64> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let num: u64 = num.parse().ok()?;
+
+    let mult: u64 = match unit {
+        "" | "B" => 1,
+        "K" | "KiB" => 1 << 10,
+        "M" | "MiB" => 1 << 20,
+        "G" | "GiB" => 1 << 30,
+        "T" | "TiB" => 1 << 40,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "TB" => 1_000_000_000_000,
+        _ => return None,
+    };
+
+    num.checked_mul(mult)
+}
+
+/// A path's raw bytes as text: `\` becomes `\\`, newline and
+/// carriage return become `\n` and `\r`, and bytes that are not
+/// UTF-8 become `\xNN`. Distinct names never give the same string,
+/// and names without those characters are unchanged.
+pub fn escape_path(path: &OsStr) -> String {
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path);
+    #[cfg(not(unix))]
+    let lossy = path.to_string_lossy();
+    #[cfg(not(unix))]
+    let bytes = lossy.as_bytes();
+
+    let mut out = String::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(valid) => (valid, 0),
+            Err(e) => {
+                let valid = std::str::from_utf8(&rest[..e.valid_up_to()]).expect("valid prefix");
+                (valid, e.error_len().unwrap_or(rest.len() - e.valid_up_to()))
+            }
+        };
+        for c in valid.chars() {
+            match c {
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                c => out.push(c),
+            }
+        }
+        for b in &rest[valid.len()..valid.len() + invalid] {
+            out.push_str(&format!("\\x{:02x}", b));
+        }
+        rest = &rest[valid.len() + invalid..];
+    }
+    out
+}
+
+/// The inverse of [`escape_path`]; `None` for an unknown or
+/// incomplete escape.
+pub fn unescape_path(s: &str) -> Option<PathBuf> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        let (&kind, tail) = rest.split_first()?;
+        rest = tail;
+        match kind {
+            b'\\' => bytes.push(b'\\'),
+            b'n' => bytes.push(b'\n'),
+            b'r' => bytes.push(b'\r'),
+            b'x' => {
+                let hex = rest.get(..2).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+                bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                rest = &rest[2..];
+            }
+            _ => return None,
+        }
+    }
+
+    #[cfg(unix)]
+    return Some(<std::ffi::OsString as std::os::unix::ffi::OsStringExt>::from_vec(bytes).into());
+    #[cfg(not(unix))]
+    return String::from_utf8(bytes).ok().map(PathBuf::from);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_path() {
+        assert_eq!(escape_path(OsStr::new("plain/näme")), "plain/näme");
+        assert_eq!(escape_path(OsStr::new("a\\b\nc\rd")), "a\\\\b\\nc\\rd");
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            assert_eq!(escape_path(OsStr::from_bytes(b"x\xff\xe9y")), "x\\xff\\xe9y");
+            // A literal `\xff` cannot be confused with the byte
+            assert_eq!(escape_path(OsStr::new("x\\xff")), "x\\\\xff");
+        }
+    }
+
+    #[test]
+    fn test_unescape_path_round_trips() {
+        for name in ["plain", "a\\b\nc\rd", "x\\xff", "trailing\\"] {
+            let escaped = escape_path(OsStr::new(name));
+            assert_eq!(unescape_path(&escaped).unwrap(), PathBuf::from(name));
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let raw = OsStr::from_bytes(b"caf\xe9");
+            assert_eq!(unescape_path(&escape_path(raw)).unwrap(), raw);
+        }
+        for bad in ["a\\", "\\t", "\\x4", "\\xzz", "\\x+f"] {
+            assert_eq!(unescape_path(bad), None, "{:?}", bad);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+// =========================================================
+// turb1600 CLI — `--pieces <size> <file>`
+//
+// Prints `<index> <hex>` per piece as it completes, then
+// `root <hex>`. Pieces are plain turb1600 digests; the root is
+// domain-separated from them (see `turb1600::segment`).
+// =========================================================
+
+use std::fs::File;
+use std::io::{self, Read};
+
+use turb1600::SegmentHasher;
+
+const READ_BUF: usize = 1 << 20;
+
+pub fn run(piece_size: u64, path: &str) -> io::Result<()> {
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; READ_BUF];
+    let mut hasher = SegmentHasher::new(piece_size);
+    let mut printed = 0;
+
+    loop {
+        let n = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buf[..n]);
+
+        for digest in &hasher.segments()[printed..] {
+            println!("{} {}", printed, hex::encode(digest));
+            printed += 1;
+        }
+    }
+
+    let out = hasher.finalize();
+    for digest in &out.segments[printed..] {
+        println!("{} {}", printed, hex::encode(digest));
+        printed += 1;
+    }
+    println!("root {}", hex::encode(&out.root));
+
+    Ok(())
+}
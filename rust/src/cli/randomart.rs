@@ -0,0 +1,116 @@
+// =========================================================
+// turb1600 CLI — `--randomart`: OpenSSH-style visual fingerprint
+//
+// The "drunken bishop" walk from OpenSSH's sshkey.c: starting in
+// the middle of a 17×9 field, each 2-bit group (low bits first)
+// moves the bishop one step diagonally, and squares are drawn by
+// how often they were visited. The 128-byte digest is first
+// folded to 32 bytes by XOR, the length OpenSSH walks for SHA-256
+// fingerprints; a longer walk would saturate the field.
+//
+// As in OpenSSH, the frame names what was hashed: the algorithm
+// on top and the digest size in bits below, preceded by the leaf
+// size for tree mode ("[TURB1600-TREE]" over "[4MiB 1024]").
+// =========================================================
+
+use super::algorithm::{size_name, Algorithm};
+
+const WIDTH: usize = 17;
+const HEIGHT: usize = 9;
+const WALK_BYTES: usize = 32;
+
+/// Visit counts map to these; `S` and `E` mark start and end.
+const SYMBOLS: &[u8] = b" .o+=*BOX@%&#/^";
+
+/// Top and bottom frame labels for a digest of `algorithm`
+fn labels(algorithm: Algorithm, digest_len: usize) -> (String, String) {
+    let bits = digest_len * 8;
+    match algorithm {
+        Algorithm::Tree(chunk) => ("[TURB1600-TREE]".to_string(), format!("[{} {}]", size_name(chunk), bits)),
+        _ => (format!("[{}]", algorithm.name().to_uppercase()), format!("[{}]", bits)),
+    }
+}
+
+/// Render the field, including its frame, as `HEIGHT + 2` lines.
+pub fn render(algorithm: Algorithm, digest: &[u8]) -> Vec<String> {
+    let mut walk = [0u8; WALK_BYTES];
+    for (i, b) in digest.iter().enumerate() {
+        walk[i % WALK_BYTES] ^= b;
+    }
+
+    let mut field = [[0usize; WIDTH]; HEIGHT];
+    let (start_x, start_y) = (WIDTH / 2, HEIGHT / 2);
+    let (mut x, mut y) = (start_x, start_y);
+
+    for mut byte in walk {
+        for _ in 0..4 {
+            x = if byte & 1 != 0 { (x + 1).min(WIDTH - 1) } else { x.saturating_sub(1) };
+            y = if byte & 2 != 0 { (y + 1).min(HEIGHT - 1) } else { y.saturating_sub(1) };
+            field[y][x] += 1;
+            byte >>= 2;
+        }
+    }
+
+    let (top, bottom) = labels(algorithm, digest.len());
+    let mut lines = Vec::with_capacity(HEIGHT + 2);
+    lines.push(frame(&top));
+    for (row, counts) in field.iter().enumerate() {
+        let mut line = String::with_capacity(WIDTH + 2);
+        line.push('|');
+        for (col, &count) in counts.iter().enumerate() {
+            line.push(if (col, row) == (start_x, start_y) {
+                'S'
+            } else if (col, row) == (x, y) {
+                'E'
+            } else {
+                SYMBOLS[count.min(SYMBOLS.len() - 1)] as char
+            });
+        }
+        line.push('|');
+        lines.push(line);
+    }
+    lines.push(frame(&bottom));
+    lines
+}
+
+/// `+---[label]---+` with the label centred in the field width.
+fn frame(label: &str) -> String {
+    let label = &label[..label.len().min(WIDTH)];
+    let pad = WIDTH - label.len();
+    format!("+{}{}{}+", "-".repeat(pad / 2), label, "-".repeat(pad - pad / 2))
+}
+
+/// Print the art with the hex digest alongside it, 32 digits a row.
+pub fn print(algorithm: Algorithm, digest: &[u8]) {
+    let hex = hex::encode(digest);
+    let art = render(algorithm, digest);
+    let mut hex_rows = hex.as_bytes().chunks(32);
+
+    for (i, line) in art.iter().enumerate() {
+        let inside = i > 0 && i < art.len() - 1;
+        match if inside { hex_rows.next() } else { None } {
+            Some(row) => println!("{}  {}", line, String::from_utf8_lossy(row)),
+            None => println!("{}", line),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_names_algorithm_and_size() {
+        let cases = [
+            (Algorithm::Turb1600, 128, "+---[TURB1600]----+", "+-----[1024]------+"),
+            (Algorithm::Turb256, 32, "+-[TURB1600-256]--+", "+------[256]------+"),
+            (Algorithm::Tree(4 << 20), 128, "+-[TURB1600-TREE]-+", "+---[4MiB 1024]---+"),
+        ];
+        for (algorithm, len, top, bottom) in cases {
+            let art = render(algorithm, &vec![0xa5; len]);
+            assert_eq!(art.len(), HEIGHT + 2);
+            assert!(art.iter().all(|line| line.len() == WIDTH + 2));
+            assert_eq!((art[0].as_str(), art[HEIGHT + 1].as_str()), (top, bottom));
+        }
+    }
+}
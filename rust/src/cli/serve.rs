@@ -0,0 +1,386 @@
+// =========================================================
+// turb1600 CLI — `serve`: HTTP hashing endpoint
+//
+//   POST /hash[?length=N]
+//
+// The request body (Content-Length or chunked) is streamed
+// through the hasher and the hex digest is returned as
+// text/plain. `length` selects the output length in bytes; it is
+// bound into the hash (`Turb1600Builder::output_len`), so a short
+// digest is not a prefix of the default one. An
+// `X-Turb1600-Key: <hex>` header (non-empty) switches to keyed
+// mode (MAC). One request is served per connection.
+//
+// At most MAX_CONNECTIONS are served at once; more are answered
+// with 503. Each read waits at most IO_TIMEOUT and the whole
+// request must arrive within REQUEST_TIMEOUT, so a client that
+// trickles bytes cannot hold a slot forever.
+// =========================================================
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use turb1600::builder::MAX_OUTPUT_LEN;
+use turb1600::{SecretKey, Turb1600};
+
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:8080";
+
+/// Upper bound on the request line plus headers
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+const MAX_CONNECTIONS: usize = 64;
+
+/// An HTTP error response
+#[derive(Debug, PartialEq)]
+struct Status(u16, &'static str);
+
+impl From<io::Error> for Status {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => Status(408, "Request Timeout"),
+            _ => Status(400, "Bad Request"),
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    content_length: Option<u64>,
+    chunked: bool,
+    expect_continue: bool,
+    key: Option<SecretKey>,
+}
+
+/// A connection slot, released when the handler finishes
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Accept connections forever, one thread per connection
+pub fn run(addr: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("turb1600: listening on http://{}", listener.local_addr()?);
+
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("turb1600: accept failed: {}", e);
+                continue;
+            }
+        };
+        let timeouts = stream
+            .set_read_timeout(Some(IO_TIMEOUT))
+            .and_then(|()| stream.set_write_timeout(Some(IO_TIMEOUT)));
+        if timeouts.is_err() {
+            continue;
+        }
+
+        let slot = Slot(Arc::clone(&active));
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            drop(slot);
+            let _ = write_response(&mut stream, 503, "Service Unavailable", "Service Unavailable\n");
+            continue;
+        }
+        thread::spawn(move || {
+            let _slot = slot;
+            let _ = handle(stream);
+        });
+    }
+
+    Ok(())
+}
+
+/// Socket reader that gives up once the request deadline has passed
+struct DeadlineStream {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self
+            .deadline
+            .checked_duration_since(Instant::now())
+            .filter(|left| !left.is_zero())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "request deadline passed"))?;
+        self.stream.set_read_timeout(Some(left.min(IO_TIMEOUT)))?;
+        self.stream.read(buf)
+    }
+}
+
+fn handle(stream: TcpStream) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(DeadlineStream {
+        stream,
+        deadline: Instant::now() + REQUEST_TIMEOUT,
+    });
+
+    match respond(&mut reader, &mut writer) {
+        Ok(body) => write_response(&mut writer, 200, "OK", &body),
+        Err(Status(code, reason)) => write_response(&mut writer, code, reason, &format!("{}\n", reason)),
+    }
+}
+
+fn write_response(w: &mut impl Write, code: u16, reason: &str, body: &str) -> io::Result<()> {
+    write!(
+        w,
+        "HTTP/1.1 {} {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        reason,
+        body.len(),
+        body
+    )?;
+    w.flush()
+}
+
+fn respond(reader: &mut impl BufRead, writer: &mut impl Write) -> Result<String, Status> {
+    let req = read_head(reader)?;
+
+    if req.path != "/hash" {
+        return Err(Status(404, "Not Found"));
+    }
+    if req.method != "POST" {
+        return Err(Status(405, "Method Not Allowed"));
+    }
+
+    let mut length = turb1600::DIGEST_BYTES as u64;
+    for pair in req.query.split('&').filter(|p| !p.is_empty()) {
+        match pair.split_once('=') {
+            Some(("length", v)) => {
+                length = parse_number(v, 10)?;
+                if length == 0 || length > MAX_OUTPUT_LEN as u64 {
+                    return Err(Status(400, "Bad Request"));
+                }
+            }
+            _ => return Err(Status(400, "Bad Request")),
+        }
+    }
+
+    if !req.chunked && req.content_length.is_none() {
+        return Err(Status(411, "Length Required"));
+    }
+
+    let mut builder = Turb1600::builder().output_len(length as usize);
+    if let Some(key) = &req.key {
+        builder = builder.key(key);
+    }
+    let mut hasher = builder.build().map_err(|_| Status(400, "Bad Request"))?;
+
+    if req.expect_continue {
+        writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+    }
+
+    if req.chunked {
+        read_chunked(reader, &mut hasher)?;
+    } else if let Some(n) = req.content_length {
+        let copied = copy_into(&mut reader.by_ref().take(n), &mut hasher)?;
+        if copied != n {
+            return Err(Status(400, "Bad Request"));
+        }
+    }
+
+    Ok(format!("{}\n", hex::encode(hasher.finalize())))
+}
+
+/// Digits only: `str::parse` and `from_str_radix` would also take a sign
+fn parse_number(s: &str, radix: u32) -> Result<u64, Status> {
+    if s.is_empty() || !s.chars().all(|c| c.is_digit(radix)) {
+        return Err(Status(400, "Bad Request"));
+    }
+    u64::from_str_radix(s, radix).map_err(|_| Status(400, "Bad Request"))
+}
+
+fn read_line(reader: &mut impl BufRead, budget: &mut usize) -> Result<String, Status> {
+    let mut line = String::new();
+    let n = reader
+        .by_ref()
+        .take(*budget as u64)
+        .read_line(&mut line)?;
+    if n == *budget && !line.ends_with('\n') {
+        return Err(Status(431, "Request Header Fields Too Large"));
+    }
+    if n == 0 || !line.ends_with('\n') {
+        return Err(Status(400, "Bad Request"));
+    }
+    *budget -= n;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn read_head(reader: &mut impl BufRead) -> Result<Request, Status> {
+    let mut budget = MAX_HEAD_BYTES;
+    let line = read_line(reader, &mut budget)?;
+
+    let mut parts = line.split(' ');
+    let (method, target) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(m), Some(t), Some(v)) if v.starts_with("HTTP/1.") => (m, t),
+        _ => return Err(Status(400, "Bad Request")),
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut req = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        content_length: None,
+        chunked: false,
+        expect_continue: false,
+        key: None,
+    };
+
+    loop {
+        let line = read_line(reader, &mut budget)?;
+        if line.is_empty() {
+            break;
+        }
+
+        let (name, value) = line.split_once(':').ok_or(Status(400, "Bad Request"))?;
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                req.content_length = Some(parse_number(value, 10)?);
+            }
+            "transfer-encoding" => req.chunked = value.eq_ignore_ascii_case("chunked"),
+            "expect" => req.expect_continue = value.eq_ignore_ascii_case("100-continue"),
+            "x-turb1600-key" => {
+                let bytes = hex::decode(value).map_err(|_| Status(400, "Bad Request"))?;
+                if bytes.is_empty() {
+                    return Err(Status(400, "Bad Request"));
+                }
+                req.key = Some(SecretKey::from(bytes));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(req)
+}
+
+fn read_chunked(reader: &mut impl BufRead, hasher: &mut Turb1600) -> Result<(), Status> {
+    loop {
+        let mut budget = MAX_HEAD_BYTES;
+        let line = read_line(reader, &mut budget)?;
+        let size = parse_number(line.split(';').next().unwrap_or("").trim(), 16)?;
+
+        if size == 0 {
+            // Skip trailers up to the terminating empty line
+            while !read_line(reader, &mut budget)?.is_empty() {}
+            return Ok(());
+        }
+
+        if copy_into(&mut reader.by_ref().take(size), hasher)? != size {
+            return Err(Status(400, "Bad Request"));
+        }
+        if !read_line(reader, &mut budget)?.is_empty() {
+            return Err(Status(400, "Bad Request"));
+        }
+    }
+}
+
+fn copy_into(reader: &mut impl Read, hasher: &mut Turb1600) -> io::Result<u64> {
+    let mut buf = [0u8; 64 * 1024];
+    let mut total = 0;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return Ok(total),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buf[..n]);
+        total += n as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(head: &str, body: &[u8]) -> (Result<String, Status>, Vec<u8>) {
+        let mut request = format!("POST /hash HTTP/1.1\r\n{}\r\n", head).into_bytes();
+        request.extend_from_slice(body);
+        let mut written = Vec::new();
+        let result = respond(&mut &request[..], &mut written);
+        (result, written)
+    }
+
+    fn hex_digest(data: &[u8], len: usize) -> String {
+        let mut hasher = Turb1600::builder().output_len(len).build().unwrap();
+        hasher.update(data);
+        format!("{}\n", hex::encode(hasher.finalize()))
+    }
+
+    #[test]
+    fn test_content_length_chunked_and_continue() {
+        let expected = hex_digest(b"hello world", turb1600::DIGEST_BYTES);
+        assert_eq!(post("Content-Length: 11\r\n", b"hello world").0, Ok(expected.clone()));
+
+        let chunked = b"5;ext=1\r\nhello\r\n6\r\n world\r\n0\r\nX-Trailer: t\r\n\r\n";
+        let (result, written) = post("Transfer-Encoding: chunked\r\nExpect: 100-continue\r\n", chunked);
+        assert_eq!(result, Ok(expected));
+        assert_eq!(written, b"HTTP/1.1 100 Continue\r\n\r\n");
+
+        // The length is bound in, not a truncation of the default digest
+        let request = b"POST /hash?length=32 HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc";
+        let short = respond(&mut &request[..], &mut Vec::new()).unwrap();
+        assert_eq!(short, hex_digest(b"abc", 32));
+        assert_ne!(short[..64], hex_digest(b"abc", turb1600::DIGEST_BYTES)[..64]);
+        let request = b"POST /hash?length=300 HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc";
+        assert_eq!(respond(&mut &request[..], &mut Vec::new()), Ok(hex_digest(b"abc", 300)));
+
+        let request = b"POST /hash HTTP/1.1\r\nx-turb1600-key: 6b6579\r\ncontent-length: 3\r\n\r\nabc";
+        let mac = turb1600::turb1600_mac(&SecretKey::new(b"key"), b"abc");
+        assert_eq!(respond(&mut &request[..], &mut Vec::new()), Ok(format!("{}\n", hex::encode(mac))));
+    }
+
+    #[test]
+    fn test_bad_lengths_are_rejected() {
+        let bad = Err(Status(400, "Bad Request"));
+        for head in ["Content-Length: abc\r\n", "Content-Length: +3\r\n", "Content-Length: \r\n"] {
+            assert_eq!(post(head, b"abc").0, bad, "{}", head);
+        }
+        // Body shorter than declared
+        assert_eq!(post("Content-Length: 10\r\n", b"abc").0, bad);
+        assert_eq!(post("", b"abc").0, Err(Status(411, "Length Required")));
+
+        for body in [&b"+3\r\nabc\r\n0\r\n\r\n"[..], b"zz\r\n", b"3\r\nabcd\r\n0\r\n\r\n", b"5\r\nabc"] {
+            assert_eq!(post("Transfer-Encoding: chunked\r\n", body).0, bad);
+        }
+
+        let long_key = format!("X-Turb1600-Key: {}\r\n", "00".repeat(turb1600::builder::MAX_KEY_BYTES + 1));
+        for head in ["X-Turb1600-Key:\r\n", "X-Turb1600-Key: zz\r\n", &long_key] {
+            assert_eq!(post(&format!("{}Content-Length: 0\r\n", head), b"").0, bad, "{}", head);
+        }
+
+        for query in ["length=0", "length=-1", "length=1048577", "other=1"] {
+            let request = format!("POST /hash?{} HTTP/1.1\r\nContent-Length: 0\r\n\r\n", query);
+            assert_eq!(respond(&mut request.as_bytes(), &mut Vec::new()), bad, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_head_limits_and_routing() {
+        let huge = format!("X-Padding: {}\r\n", "a".repeat(MAX_HEAD_BYTES));
+        assert_eq!(post(&huge, b"").0, Err(Status(431, "Request Header Fields Too Large")));
+
+        let respond_to = |request: &str| respond(&mut request.as_bytes(), &mut Vec::new());
+        assert_eq!(respond_to("GET /hash HTTP/1.1\r\n\r\n"), Err(Status(405, "Method Not Allowed")));
+        assert_eq!(respond_to("POST /other HTTP/1.1\r\n\r\n"), Err(Status(404, "Not Found")));
+        assert_eq!(respond_to("POST /hash\r\n\r\n"), Err(Status(400, "Bad Request")));
+        assert_eq!(respond_to("POST /hash HTTP/1.1\r\nno colon\r\n\r\n"), Err(Status(400, "Bad Request")));
+        // Connection closed mid-head
+        assert_eq!(respond_to("POST /hash HTTP/1.1\r\nContent-Le"), Err(Status(400, "Bad Request")));
+    }
+}
@@ -0,0 +1,42 @@
+// =========================================================
+// turb1600 CLI — `--tee`: hash a pipeline in passing
+//
+//   producer | turb1600 --tee [--digest-file <file>] | consumer
+//
+// stdin is copied to stdout unchanged while it is hashed; at EOF
+// the hex digest goes to stderr, or to the given file.
+// =========================================================
+
+use std::fs;
+use std::io::{self, Read, Write};
+
+use turb1600::file::FILE_BUFFER_DEFAULT;
+use turb1600::Turb1600;
+
+pub fn run(digest_file: Option<&str>) -> io::Result<()> {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    let mut buf = vec![0u8; FILE_BUFFER_DEFAULT];
+    let mut hasher = Turb1600::new();
+
+    loop {
+        let n = match stdin.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buf[..n]);
+        stdout.write_all(&buf[..n])?;
+    }
+    stdout.flush()?;
+
+    let digest = hex::encode(hasher.finalize());
+    match digest_file {
+        Some(path) => fs::write(path, format!("{}\n", digest)),
+        None => {
+            eprintln!("{}", digest);
+            Ok(())
+        }
+    }
+}
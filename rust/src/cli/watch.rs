@@ -0,0 +1,185 @@
+// =========================================================
+// turb1600 CLI — `watch`: re-hash files as they change
+//
+//   turb1600 watch [--manifest <file>] <path>...
+//
+// Files under the given paths are hashed once, then again after
+// filesystem notifications for them settle (no new events for
+// SETTLE). A line is printed only when a digest actually changes,
+// so the bursts of events one save produces give a single report:
+//
+//   <hex>  <path>        (new or changed digest)
+//   REMOVED  <path>
+//
+// With a manifest, each file is instead reported against its
+// recorded digest as `OK`, `CHANGED`, `UNEXPECTED` (not in the
+// manifest) or `REMOVED`, so tampering stands out. Files are
+// hashed with the algorithm their manifest entry names.
+// =========================================================
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use notify::{RecursiveMode, Watcher};
+
+use super::algorithm::Algorithm;
+use super::manifest;
+
+const SETTLE: Duration = Duration::from_millis(200);
+
+struct State {
+    /// Last digest seen per file (absolute path)
+    current: HashMap<PathBuf, Vec<u8>>,
+    /// Expected digests, when watching against a manifest
+    expected: Option<HashMap<PathBuf, (Algorithm, Vec<u8>)>>,
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| {
+        std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    })
+}
+
+impl State {
+    fn report(&self, path: &Path, digest: Option<&[u8]>) {
+        let expected = match &self.expected {
+            None => {
+                match digest {
+                    Some(d) => println!("{}", manifest::line(Algorithm::Turb1600, d, path)),
+                    None => println!("REMOVED  {}", manifest::shown(path)),
+                }
+                return;
+            }
+            Some(expected) => expected.get(path),
+        };
+
+        let status = match (digest, expected) {
+            (Some(d), Some((_, e))) if d == e.as_slice() => "OK",
+            (Some(_), Some(_)) => "CHANGED",
+            (Some(_), None) => "UNEXPECTED",
+            (None, _) => "REMOVED",
+        };
+        println!("{}: {}", manifest::shown(path), status);
+    }
+
+    /// Re-hash `path` (a file, a directory to scan, or a path that is gone)
+    fn refresh(&mut self, path: &Path) {
+        let meta = match fs::symlink_metadata(path) {
+            Ok(meta) => meta,
+            Err(_) => {
+                // Gone: report the file, or every known file below a removed directory
+                let gone: Vec<PathBuf> = self
+                    .current
+                    .keys()
+                    .filter(|known| known.starts_with(path))
+                    .cloned()
+                    .collect();
+                for known in gone {
+                    self.current.remove(&known);
+                    self.report(&known, None);
+                }
+                return;
+            }
+        };
+
+        if meta.is_dir() {
+            if let Ok(items) = fs::read_dir(path) {
+                for item in items.flatten() {
+                    self.refresh(&item.path());
+                }
+            }
+            return;
+        }
+        if !meta.is_file() {
+            return;
+        }
+
+        let algorithm = match &self.expected {
+            Some(expected) => expected.get(path).map_or(Algorithm::Turb1600, |(a, _)| *a),
+            None => Algorithm::Turb1600,
+        };
+        let digest = match algorithm.hash_file(path) {
+            Ok(d) => d,
+            // Often a file caught mid-replace; the next event re-hashes it
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                return;
+            }
+        };
+        if self.current.get(path) != Some(&digest) {
+            self.report(path, Some(&digest));
+            self.current.insert(path.to_path_buf(), digest);
+        }
+    }
+}
+
+pub fn run(manifest_path: Option<&str>, paths: &[String]) -> Result<(), String> {
+    let expected = match manifest_path {
+        Some(file) => Some(
+            manifest::load(file)?
+                .entries
+                .into_iter()
+                .map(|entry| (absolute(&entry.path), (entry.algorithm, entry.digest)))
+                .collect(),
+        ),
+        None => None,
+    };
+    let mut state = State {
+        current: HashMap::new(),
+        expected,
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+
+    let roots: Vec<PathBuf> = paths.iter().map(|p| absolute(Path::new(p))).collect();
+    for root in &roots {
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .map_err(|e| format!("{}: {}", root.display(), e))?;
+    }
+    for root in &roots {
+        state.refresh(root);
+    }
+
+    // Manifest entries under a watched root that are already missing
+    if let Some(expected) = &state.expected {
+        for path in expected.keys() {
+            if roots.iter().any(|root| path.starts_with(root)) && !state.current.contains_key(path) {
+                state.report(path, None);
+            }
+        }
+    }
+
+    let mut pending: BTreeSet<PathBuf> = BTreeSet::new();
+    loop {
+        let event = if pending.is_empty() {
+            match rx.recv() {
+                Ok(event) => event,
+                Err(_) => return Ok(()),
+            }
+        } else {
+            match rx.recv_timeout(SETTLE) {
+                Ok(event) => event,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    for path in std::mem::take(&mut pending) {
+                        state.refresh(&path);
+                    }
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        };
+
+        match event {
+            Ok(event) if !event.kind.is_access() => pending.extend(event.paths),
+            Ok(_) => {}
+            Err(e) => eprintln!("turb1600: watch: {}", e),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+// =========================================================
+// turb1600 — Compile-time hashing
+//
+// A `const fn` port of the one-shot hash, for baking digests of
+// static assets into a binary:
+//
+//   const ID: [u8; 128] = turb1600_hash_const(b"firmware v1");
+//   let logo = turb1600!(include_bytes!("logo.png"));
+//
+// It follows core.rs step for step but passes the state by value
+// and loops with `while`. The runtime permutation takes the state
+// as `&mut`, which `const fn` only accepts from Rust 1.83 (the MSRV
+// is 1.70); it is reached through `backend::rounds`, a function
+// pointer selected at run time; and `for` loops over iterators
+// are not allowed in `const fn` at all.
+//
+// Const evaluation runs at a few KiB per second of build time.
+// Past ~8 KiB rustc's `long_running_const_eval` lint fires; the
+// macro allows it, direct `const` callers must do so themselves.
+// =========================================================
+
+use crate::core::{
+    rot_offset, round_constant, BLOCK_BYTES, BLOCK_LANES, DIGEST_BYTES, INIT_TAG, LANES,
+    PERM_TABLE, ROT_TABLE, ROUNDS_FINAL, ROUNDS_MAIN,
+};
+
+const fn permute(s: [u64; LANES], round: usize) -> [u64; LANES] {
+    let mut s = s;
+
+    // ---- column mixing ----
+    let mut c = [0u64; 5];
+    let mut x = 0;
+    while x < 5 {
+        c[x] = s[x] ^ s[x + 5] ^ s[x + 10] ^ s[x + 15] ^ s[x + 20];
+        x += 1;
+    }
+
+    let mut i = 0;
+    while i < LANES {
+        let x = i % 5;
+        s[i] ^= c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+        i += 1;
+    }
+
+    // ---- rotation + permutation ----
+    let mut t = [0u64; LANES];
+    let mut i = 0;
+    while i < LANES {
+        t[PERM_TABLE[i]] = s[i].rotate_left(rot_offset(round, ROT_TABLE[i]));
+        i += 1;
+    }
+
+    // ---- nonlinear layer ----
+    let mut i = 0;
+    while i < LANES {
+        let row = i - i % 5;
+        let b = t[row + (i + 1) % 5];
+        let c = t[row + (i + 2) % 5];
+        s[i] = t[i] ^ (!b & c);
+        i += 1;
+    }
+
+    // ---- round injection ----
+    s[(round * 7) % LANES] ^= round_constant(round);
+    s
+}
+
+/// XOR one padded rate block starting at `data[off..]` into the
+/// state. Bytes past `len` are taken as zero, then the 0x01 … 0x80
+/// padding is applied if `pad` is set.
+const fn absorb(
+    s: [u64; LANES],
+    data: &[u8],
+    off: usize,
+    len: usize,
+    pad: bool,
+) -> [u64; LANES] {
+    let mut block = [0u8; BLOCK_BYTES];
+    let mut i = 0;
+    while i < len {
+        block[i] = data[off + i];
+        i += 1;
+    }
+    if pad {
+        block[len] = 0x01;
+        block[BLOCK_BYTES - 1] |= 0x80;
+    }
+
+    let mut s = s;
+    let mut lane = 0;
+    while lane < BLOCK_LANES {
+        let mut w = [0u8; 8];
+        let mut b = 0;
+        while b < 8 {
+            w[b] = block[lane * 8 + b];
+            b += 1;
+        }
+        s[lane] ^= u64::from_le_bytes(w);
+        lane += 1;
+    }
+    s
+}
+
+const fn rounds(s: [u64; LANES], first: usize, n: usize) -> [u64; LANES] {
+    let mut s = s;
+    let mut r = first;
+    while r < first + n {
+        s = permute(s, r);
+        r += 1;
+    }
+    s
+}
+
+/// turb1600 digest of `data`, evaluable in `const` context.
+///
+/// Matches [`turb1600_hash`](crate::turb1600_hash) for every input.
+pub const fn turb1600_hash_const(data: &[u8]) -> [u8; DIGEST_BYTES] {
+    let tag_len = if INIT_TAG.len() < BLOCK_BYTES - 1 {
+        INIT_TAG.len()
+    } else {
+        BLOCK_BYTES - 1
+    };
+    let mut s = absorb([0u64; LANES], INIT_TAG, 0, tag_len, true);
+    s = rounds(s, 0, 8);
+
+    let mut round = 0;
+    let mut off = 0;
+    while data.len() - off >= BLOCK_BYTES {
+        s = absorb(s, data, off, BLOCK_BYTES, false);
+        s = rounds(s, round, ROUNDS_MAIN);
+        round += ROUNDS_MAIN;
+        off += BLOCK_BYTES;
+    }
+
+    s = absorb(s, data, off, data.len() - off, true);
+    s = rounds(s, round, ROUNDS_MAIN + ROUNDS_FINAL);
+
+    // DIGEST_BYTES fits in one rate block, so a single squeeze suffices
+    s[LANES - 1] ^= u64::MAX;
+    let mut out = [0u8; DIGEST_BYTES];
+    let mut i = 0;
+    while i < DIGEST_BYTES {
+        out[i] = s[i / 8].to_le_bytes()[i % 8];
+        i += 1;
+    }
+    out
+}
+
+/// Adapts the literal forms accepted by [`turb1600!`] to `&[u8]`.
+#[doc(hidden)]
+pub struct ConstInput<T>(pub T);
+
+impl ConstInput<&'static str> {
+    pub const fn as_bytes(self) -> &'static [u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl ConstInput<&'static [u8]> {
+    pub const fn as_bytes(self) -> &'static [u8] {
+        self.0
+    }
+}
+
+impl<const N: usize> ConstInput<&'static [u8; N]> {
+    pub const fn as_bytes(self) -> &'static [u8] {
+        self.0
+    }
+}
+
+/// Hash a string literal, byte string or `include_bytes!` at
+/// compile time, yielding a `[u8; DIGEST_BYTES]`.
+///
+/// Large assets still compile, with rustc printing progress
+/// warnings, but slowly (see the module docs).
+///
+/// ```
+/// const HELLO: [u8; turb1600::DIGEST_BYTES] = turb1600::turb1600!("hello");
+/// assert_eq!(HELLO.to_vec(), turb1600::turb1600_hash(b"hello"));
+/// ```
+#[macro_export]
+macro_rules! turb1600 {
+    ($data:expr) => {{
+        // `unknown_lints`: the lint only exists from Rust 1.72
+        #[allow(unknown_lints, long_running_const_eval)]
+        const DIGEST: [u8; $crate::DIGEST_BYTES] = $crate::const_hash::turb1600_hash_const(
+            $crate::const_hash::ConstInput($data).as_bytes(),
+        );
+        DIGEST
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_const_matches_runtime() {
+        let data: Vec<u8> = (0..600usize).map(|i| (i * 31 + 7) as u8).collect();
+        for len in [0, 1, 8, 135, 136, 137, 299, 409, 600] {
+            assert_eq!(
+                turb1600_hash_const(&data[..len]).to_vec(),
+                crate::turb1600_hash(&data[..len]),
+                "len {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_macro_forms() {
+        const FROM_STR: [u8; DIGEST_BYTES] = crate::turb1600!("abc");
+        assert_eq!(FROM_STR.to_vec(), crate::turb1600_hash(b"abc"));
+        assert_eq!(crate::turb1600!(b"abc"), FROM_STR);
+
+        const SLICE: &[u8] = b"abc";
+        assert_eq!(crate::turb1600!(SLICE), FROM_STR);
+    }
+}
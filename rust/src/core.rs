@@ -7,33 +7,58 @@
 
 #![allow(clippy::needless_range_loop)]
 
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use crate::backend;
+use crate::error::Turb1600Error;
+use crate::key::{zeroize, zeroize_lanes};
+
 // =========================================================
 // Core parameters
 // =========================================================
 
-const LANES: usize = 25;                // 1600-bit state
-const BLOCK_BYTES: usize = 136;         // 1088-bit rate
-const BLOCK_LANES: usize = BLOCK_BYTES / 8;
+pub(crate) const LANES: usize = 25;         // 1600-bit state
+pub(crate) const BLOCK_BYTES: usize = 136;  // 1088-bit rate
+pub(crate) const BLOCK_LANES: usize = BLOCK_BYTES / 8;
 
-const ROUNDS_MAIN: usize = 36;          // increased diffusion
-const ROUNDS_FINAL: usize = 6;          // stronger finalization
-const OUT_BYTES: usize = 128;           // 1024-bit output
+pub(crate) const ROUNDS_MAIN: usize = 36;   // increased diffusion
+pub(crate) const ROUNDS_FINAL: usize = 6;   // stronger finalization
+const OUT_BYTES: usize = 128;               // 1024-bit output
 
-// Domain separation seed
-const INIT_TAG: &[u8] =
+// Domain separation seeds
+pub(crate) const INIT_TAG: &[u8] =
     b"turb1600|sponge|1600|1088|512|1024|release";
+pub(crate) const MAC_TAG: &[u8] =
+    b"turb1600|sponge|1600|1088|512|1024|mac";
+pub(crate) const KDF_TAG: &[u8] =
+    b"turb1600|sponge|1600|1088|512|1024|kdf";
+pub(crate) const RNG_TAG: &[u8] =
+    b"turb1600|sponge|1600|1088|512|1024|drbg";
+pub(crate) const PARAM_TAG: &[u8] =
+    b"turb1600|sponge|1600|1088|512|1024|param";
+pub(crate) const DIR_TAG: &[u8] =
+    b"turb1600|sponge|1600|1088|512|1024|dir";
+pub(crate) const TREE_TAG: &[u8] =
+    b"turb1600|sponge|1600|1088|512|1024|tree";
+pub(crate) const SEGMENT_TAG: &[u8] =
+    b"turb1600|sponge|1600|1088|512|1024|segment";
+pub(crate) const TURB256_TAG: &[u8] =
+    b"turb1600|sponge|1600|1088|512|256|release";
+pub(crate) const TURB512_TAG: &[u8] =
+    b"turb1600|sponge|1600|1088|512|512|release";
 
 // =========================================================
 // Rotation utilities
 // =========================================================
 
 #[inline(always)]
-fn rotl(x: u64, r: u32) -> u64 {
+const fn rotl(x: u64, r: u32) -> u64 {
     x.rotate_left(r)
 }
 
 #[inline(always)]
-fn rot_offset(round: usize, base: u32) -> u32 {
+pub(crate) const fn rot_offset(round: usize, base: u32) -> u32 {
     base.wrapping_add(((round as u32) * 13) & 63)
 }
 
@@ -42,7 +67,7 @@ fn rot_offset(round: usize, base: u32) -> u32 {
 // =========================================================
 
 #[inline(always)]
-fn round_constant(idx: usize) -> u64 {
+pub(crate) const fn round_constant(idx: usize) -> u64 {
     let mut x = (idx as u64)
         ^ 0xA5A5A5A5A5A5A5A5
         ^ ((idx as u64).rotate_left(23));
@@ -59,7 +84,7 @@ fn round_constant(idx: usize) -> u64 {
 // Permutation tables
 // =========================================================
 
-const ROT_TABLE: [u32; LANES] = [
+pub(crate) const ROT_TABLE: [u32; LANES] = [
     0, 1, 62, 28, 27,
     36, 44, 6, 55, 20,
     3, 10, 43, 25, 39,
@@ -67,7 +92,7 @@ const ROT_TABLE: [u32; LANES] = [
     18, 2, 61, 56, 14,
 ];
 
-const PERM_TABLE: [usize; LANES] = [
+pub(crate) const PERM_TABLE: [usize; LANES] = [
     0, 7, 14, 21, 3,
     10, 17, 24, 6, 13,
     20, 2, 9, 16, 23,
@@ -75,105 +100,304 @@ const PERM_TABLE: [usize; LANES] = [
     15, 22, 4, 11, 18,
 ];
 
+/// Source lane of each output lane of the permutation step:
+/// `INV_PERM_TABLE[PERM_TABLE[i]] == i`.
+pub(crate) const INV_PERM_TABLE: [usize; LANES] = {
+    let mut inv = [0usize; LANES];
+    let mut i = 0;
+    while i < LANES {
+        inv[PERM_TABLE[i]] = i;
+        i += 1;
+    }
+    inv
+};
+
 // =========================================================
 // State seeding
 // =========================================================
 
 #[inline(always)]
-fn seed_state(tmp: &mut [u64; LANES]) -> [u64; LANES] {
+fn seed_state(tag: &[u8]) -> [u64; LANES] {
     let mut s = [0u64; LANES];
     let mut buf = [0u8; BLOCK_BYTES];
 
-    let n = INIT_TAG.len().min(BLOCK_BYTES);
-    buf[..n].copy_from_slice(&INIT_TAG[..n]);
+    let n = tag.len().min(BLOCK_BYTES - 1);
+    buf[..n].copy_from_slice(&tag[..n]);
     buf[n] = 0x01;
     buf[BLOCK_BYTES - 1] |= 0x80;
 
     absorb_block(&mut s, &buf);
 
     for r in 0..8 {
-        permute(&mut s, tmp, r);
+        permute(&mut s, r);
     }
 
     s
 }
 
+/// The default IV, as the lanes the hash starts absorbing into.
+#[cfg(feature = "gpu")]
+pub(crate) fn initial_state() -> [u64; LANES] {
+    seed_state(INIT_TAG)
+}
+
 // =========================================================
 // Absorption
 // =========================================================
 
+// Lanes are always read as little-endian words, so the digest
+// is the same on big- and little-endian targets.
 #[inline(always)]
 fn absorb_block(state: &mut [u64; LANES], block: &[u8]) {
-    unsafe {
-        let sp = state.as_mut_ptr();
-        let bp = block.as_ptr() as *const u64;
-
-        for i in 0..BLOCK_LANES {
-            *sp.add(i) ^= u64::from_le(*bp.add(i));
+    let block: &[u8; BLOCK_BYTES] = block[..BLOCK_BYTES].try_into().unwrap();
+
+    // Little-endian targets load the rate as native words directly.
+    #[cfg(target_endian = "little")]
+    let w: [u64; BLOCK_LANES] =
+        // SAFETY: `block` is exactly BLOCK_LANES * 8 bytes and the read
+        // tolerates any alignment; every bit pattern is a valid u64.
+        unsafe { (block.as_ptr() as *const [u64; BLOCK_LANES]).read_unaligned() };
+
+    #[cfg(target_endian = "big")]
+    let w: [u64; BLOCK_LANES] = {
+        let mut w = [0u64; BLOCK_LANES];
+        for (lane, word) in w.iter_mut().zip(block.chunks_exact(8)) {
+            *lane = u64::from_le_bytes(word.try_into().unwrap());
         }
+        w
+    };
+
+    macro_rules! xor_lanes {
+        ($($i:literal)*) => { $( state[$i] ^= w[$i]; )* };
     }
+    xor_lanes!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16);
 }
 
 // =========================================================
 // Core permutation
 // =========================================================
 
+// One fused pass per round: theta's column parities are folded
+// into the rotation + permutation step, which writes straight into
+// locals that the nonlinear layer reads, so the state is loaded
+// and stored once per round with no scratch copy. With constant
+// indices every table lookup below resolves at compile time.
+
 #[inline(always)]
-fn permute(state: &mut [u64; LANES], tmp: &mut [u64; LANES], round: usize) {
-    unsafe {
-        let s = state.as_mut_ptr();
+pub(crate) fn permute(s: &mut [u64; LANES], round: usize) {
+    // ---- column mixing ----
+    let c = [
+        s[0] ^ s[5] ^ s[10] ^ s[15] ^ s[20],
+        s[1] ^ s[6] ^ s[11] ^ s[16] ^ s[21],
+        s[2] ^ s[7] ^ s[12] ^ s[17] ^ s[22],
+        s[3] ^ s[8] ^ s[13] ^ s[18] ^ s[23],
+        s[4] ^ s[9] ^ s[14] ^ s[19] ^ s[24],
+    ];
+
+    let d = [
+        c[4] ^ rotl(c[1], 1),
+        c[0] ^ rotl(c[2], 1),
+        c[1] ^ rotl(c[3], 1),
+        c[2] ^ rotl(c[4], 1),
+        c[3] ^ rotl(c[0], 1),
+    ];
+
+    // ---- rotation + permutation, output lane by output lane ----
+    let r = rot_offset(round, 0);
+    let mut b = [0u64; LANES];
+    macro_rules! rho_pi {
+        ($($j:literal)*) => { $(
+            b[$j] = rotl(
+                s[INV_PERM_TABLE[$j]] ^ d[INV_PERM_TABLE[$j] % 5],
+                ROT_TABLE[INV_PERM_TABLE[$j]].wrapping_add(r),
+            );
+        )* };
+    }
+    rho_pi!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24);
+
+    // ---- nonlinear layer ----
+    macro_rules! chi {
+        ($($i:literal)*) => { $(
+            s[$i]     = b[$i]     ^ (!b[$i + 1] & b[$i + 2]);
+            s[$i + 1] = b[$i + 1] ^ (!b[$i + 2] & b[$i + 3]);
+            s[$i + 2] = b[$i + 2] ^ (!b[$i + 3] & b[$i + 4]);
+            s[$i + 3] = b[$i + 3] ^ (!b[$i + 4] & b[$i]);
+            s[$i + 4] = b[$i + 4] ^ (!b[$i]     & b[$i + 1]);
+        )* };
+    }
+    chi!(0 5 10 15 20);
+
+    // ---- round injection ----
+    s[(round * 7) % LANES] ^= round_constant(round);
+}
+
+// =========================================================
+// Incremental sponge
+//
+// A keyed sponge's state is as good as its key, and its buffer
+// briefly holds key bytes, so both are wiped: the buffer after
+// `pad_block` (which ends every key, parameter and passphrase
+// absorption) and everything on drop, including the copies that
+// `Turb1600::reset` templates and pools keep.
+// =========================================================
+
+#[derive(Clone)]
+pub(crate) struct Sponge {
+    state: [u64; LANES],
+    /// Scratch lanes for backends that permute out of place
+    tmp: [u64; LANES],
+    round: usize,
+    buf: [u8; BLOCK_BYTES],
+    buf_len: usize,
+}
+
+impl Sponge {
+    pub(crate) fn new() -> Self {
+        Self::with_tag(INIT_TAG)
+    }
+
+    /// Start from the IV derived from a domain separation `tag`.
+    pub(crate) fn with_tag(tag: &[u8]) -> Self {
+        Self {
+            state: seed_state(tag),
+            tmp: [0u64; LANES],
+            round: 0,
+            buf: [0u8; BLOCK_BYTES],
+            buf_len: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn rounds(&mut self, n: usize) {
+        backend::rounds(&mut self.state, &mut self.tmp, self.round, n);
+        self.round += n;
+    }
+
+    pub(crate) fn update(&mut self, mut data: &[u8]) {
+        if self.buf_len > 0 {
+            let n = (BLOCK_BYTES - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+
+            if self.buf_len < BLOCK_BYTES {
+                return;
+            }
 
-        // ---- column mixing ----
-        let c = [
-            *s.add(0) ^ *s.add(5) ^ *s.add(10) ^ *s.add(15) ^ *s.add(20),
-            *s.add(1) ^ *s.add(6) ^ *s.add(11) ^ *s.add(16) ^ *s.add(21),
-            *s.add(2) ^ *s.add(7) ^ *s.add(12) ^ *s.add(17) ^ *s.add(22),
-            *s.add(3) ^ *s.add(8) ^ *s.add(13) ^ *s.add(18) ^ *s.add(23),
-            *s.add(4) ^ *s.add(9) ^ *s.add(14) ^ *s.add(19) ^ *s.add(24),
-        ];
+            absorb_block(&mut self.state, &self.buf);
+            self.rounds(ROUNDS_MAIN);
+            self.buf_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(BLOCK_BYTES);
+        for block in &mut blocks {
+            absorb_block(&mut self.state, block);
+            self.rounds(ROUNDS_MAIN);
+        }
 
-        let d = [
-            c[4] ^ rotl(c[1], 1),
-            c[0] ^ rotl(c[2], 1),
-            c[1] ^ rotl(c[3], 1),
-            c[2] ^ rotl(c[4], 1),
-            c[3] ^ rotl(c[0], 1),
-        ];
+        let rem = blocks.remainder();
+        self.buf[..rem.len()].copy_from_slice(rem);
+        self.buf_len = rem.len();
+    }
 
-        for i in (0..LANES).step_by(5) {
-            *s.add(i)     ^= d[0];
-            *s.add(i + 1) ^= d[1];
-            *s.add(i + 2) ^= d[2];
-            *s.add(i + 3) ^= d[3];
-            *s.add(i + 4) ^= d[4];
+    /// Zero-fill and absorb any buffered bytes so the next update
+    /// starts on a fresh block, then wipe the buffer.
+    pub(crate) fn pad_block(&mut self) {
+        if self.buf_len > 0 {
+            self.buf[self.buf_len..].fill(0);
+            absorb_block(&mut self.state, &self.buf);
+            self.rounds(ROUNDS_MAIN);
+            self.buf_len = 0;
         }
+        zeroize(&mut self.buf);
+    }
+
+    /// Pad, run the final rounds and switch to squeezing.
+    pub(crate) fn finalize_xof(mut self) -> XofReader {
+        let rem = self.buf_len;
+        self.buf[rem..].fill(0);
+        self.buf[rem] = 0x01;
+        self.buf[BLOCK_BYTES - 1] |= 0x80;
+
+        absorb_block(&mut self.state, &self.buf);
+        self.rounds(ROUNDS_MAIN + ROUNDS_FINAL);
 
-        // ---- rotation + permutation ----
-        for i in 0..LANES {
-            *tmp.get_unchecked_mut(PERM_TABLE[i]) =
-                rotl(*s.add(i), rot_offset(round, ROT_TABLE[i]));
+        XofReader {
+            sponge: self,
+            pos: BLOCK_BYTES,
         }
+    }
+
+    /// Pad, run the final rounds and squeeze `out.len()` bytes.
+    pub(crate) fn finalize_into(self, out: &mut [u8]) {
+        self.finalize_xof().squeeze(out);
+    }
+
+    pub(crate) fn finalize(self) -> Vec<u8> {
+        let mut out = vec![0u8; OUT_BYTES];
+        self.finalize_into(&mut out);
+        out
+    }
+}
+
+impl Drop for Sponge {
+    fn drop(&mut self) {
+        zeroize_lanes(&mut self.state);
+        zeroize_lanes(&mut self.tmp);
+        zeroize(&mut self.buf);
+    }
+}
+
+// =========================================================
+// Squeezing
+// =========================================================
+
+/// Write one rate block of output into `out` (exactly `BLOCK_BYTES`)
+/// and advance the state.
+#[inline(always)]
+fn squeeze_block(sponge: &mut Sponge, out: &mut [u8]) {
+    sponge.state[LANES - 1] ^= u64::MAX;
 
-        *state = *tmp;
-        let s = state.as_mut_ptr();
+    for (word, lane) in out.chunks_exact_mut(8).zip(&sponge.state[..BLOCK_LANES]) {
+        word.copy_from_slice(&lane.to_le_bytes());
+    }
+
+    sponge.rounds(1);
+}
 
-        // ---- nonlinear layer ----
-        for i in (0..LANES).step_by(5) {
-            let a = *s.add(i);
-            let b = *s.add(i + 1);
-            let c = *s.add(i + 2);
-            let d = *s.add(i + 3);
-            let e = *s.add(i + 4);
+/// Squeeze side of a finalized sponge. Whole rate blocks are
+/// written straight into the caller's buffer; a partial block is
+/// staged in the (no longer needed) absorb buffer, with `pos` the
+/// next unread byte in it.
+#[derive(Clone)]
+pub(crate) struct XofReader {
+    sponge: Sponge,
+    pos: usize,
+}
 
-            *s.add(i)     ^= (!b) & c;
-            *s.add(i + 1) ^= (!c) & d;
-            *s.add(i + 2) ^= (!d) & e;
-            *s.add(i + 3) ^= (!e) & a;
-            *s.add(i + 4) ^= (!a) & b;
+impl XofReader {
+    pub(crate) fn squeeze(&mut self, mut out: &mut [u8]) {
+        // Drain whatever is left of a previously staged block
+        if self.pos < BLOCK_BYTES {
+            let n = (BLOCK_BYTES - self.pos).min(out.len());
+            out[..n].copy_from_slice(&self.sponge.buf[self.pos..self.pos + n]);
+            self.pos += n;
+            out = &mut out[n..];
         }
 
-        // ---- round injection ----
-        *s.add((round * 7) % LANES) ^= round_constant(round);
+        let mut blocks = out.chunks_exact_mut(BLOCK_BYTES);
+        for block in &mut blocks {
+            squeeze_block(&mut self.sponge, block);
+        }
+
+        let tail = blocks.into_remainder();
+        if !tail.is_empty() {
+            let mut buf = [0u8; BLOCK_BYTES];
+            squeeze_block(&mut self.sponge, &mut buf);
+            tail.copy_from_slice(&buf[..tail.len()]);
+            self.sponge.buf = buf;
+            self.pos = tail.len();
+        }
     }
 }
 
@@ -181,53 +405,287 @@ fn permute(state: &mut [u64; LANES], tmp: &mut [u64; LANES], round: usize) {
 // Public hashing API
 // =========================================================
 
-pub fn turb1600_hash(data: &[u8]) -> Vec<u8> {
-    let mut tmp = [0u64; LANES];
-    let mut state = seed_state(&mut tmp);
-    let mut round = 0usize;
+/// Digest size in bytes.
+pub const DIGEST_BYTES: usize = OUT_BYTES;
+
+/// Incremental turb1600 hasher.
+///
+/// Feeding the same bytes through any sequence of `update` calls
+/// gives the same digest as [`turb1600_hash`].
+#[derive(Clone)]
+pub struct Turb1600 {
+    sponge: Sponge,
+    /// State right after construction, restored by `reset`
+    init: Sponge,
+    out_len: usize,
+    /// Bytes absorbed so far, reported to `progress`
+    absorbed: u64,
+    progress: Option<Arc<dyn Fn(u64) + Send + Sync>>,
+}
+
+impl Turb1600 {
+    pub fn new() -> Self {
+        Self::from_sponge(Sponge::new())
+    }
 
-    let mut pos = 0;
-    while pos + BLOCK_BYTES <= data.len() {
-        absorb_block(&mut state, &data[pos..pos + BLOCK_BYTES]);
-        for _ in 0..ROUNDS_MAIN {
-            permute(&mut state, &mut tmp, round);
-            round += 1;
+    pub fn update(&mut self, data: &[u8]) {
+        self.sponge.update(data);
+        self.absorbed += data.len() as u64;
+        if let Some(progress) = &self.progress {
+            progress(self.absorbed);
         }
-        pos += BLOCK_BYTES;
     }
 
-    let mut tail = [0u8; BLOCK_BYTES];
-    let rem = data.len() - pos;
-    tail[..rem].copy_from_slice(&data[pos..]);
-    tail[rem] = 0x01;
-    tail[BLOCK_BYTES - 1] |= 0x80;
+    /// Call `f` with the total number of bytes absorbed after every
+    /// update, so frontends can report progress without wrapping
+    /// their readers. [`update_reader`](Self::update_reader) and the
+    /// file helpers report once per buffer.
+    ///
+    /// Clones of the hasher share the callback.
+    pub fn on_progress<F>(mut self, f: F) -> Self
+    where
+        F: Fn(u64) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(f));
+        self
+    }
+
+    /// Digest length produced by [`finalize`](Self::finalize).
+    pub fn output_len(&self) -> usize {
+        self.out_len
+    }
+
+    pub fn finalize(self) -> Vec<u8> {
+        let mut out = vec![0u8; self.out_len];
+        self.finalize_into(&mut out);
+        out
+    }
 
-    absorb_block(&mut state, &tail);
+    /// Write the digest into `out` without allocating.
+    ///
+    /// Panics unless `out.len()` equals [`output_len`](Self::output_len);
+    /// see [`try_finalize_into`](Self::try_finalize_into).
+    pub fn finalize_into(self, out: &mut [u8]) {
+        assert_eq!(out.len(), self.out_len, "output buffer has the wrong length");
+        self.sponge.finalize_into(out);
+    }
 
-    for _ in 0..(ROUNDS_MAIN + ROUNDS_FINAL) {
-        permute(&mut state, &mut tmp, round);
-        round += 1;
+    /// [`finalize_into`](Self::finalize_into), failing with
+    /// [`Turb1600Error::DigestLength`] instead of panicking.
+    pub fn try_finalize_into(self, out: &mut [u8]) -> Result<(), Turb1600Error> {
+        if out.len() != self.out_len {
+            return Err(Turb1600Error::DigestLength {
+                expected: self.out_len,
+                actual: out.len(),
+            });
+        }
+        self.sponge.finalize_into(out);
+        Ok(())
     }
 
-    let mut out = vec![0u8; OUT_BYTES];
-    let mut off = 0;
+    /// Return to the state the hasher was created in, keeping its key,
+    /// parameters and progress callback. Much cheaper than building
+    /// a new hasher, which has to derive the initial state again.
+    pub fn reset(&mut self) {
+        self.sponge.clone_from(&self.init);
+        self.absorbed = 0;
+    }
 
-    while off < OUT_BYTES {
-        state[LANES - 1] ^= u64::MAX;
+    /// [`finalize_into`](Self::finalize_into), then [`reset`](Self::reset),
+    /// so one hasher can serve request after request.
+    pub fn finalize_into_reset(&mut self, out: &mut [u8]) {
+        assert_eq!(out.len(), self.out_len, "output buffer has the wrong length");
+        let sponge = std::mem::replace(&mut self.sponge, self.init.clone());
+        self.absorbed = 0;
+        sponge.finalize_into(out);
+    }
 
-        for i in 0..BLOCK_LANES {
-            if off >= OUT_BYTES {
-                break;
-            }
-            let bytes = state[i].to_le_bytes();
-            let n = (OUT_BYTES - off).min(8);
-            out[off..off + n].copy_from_slice(&bytes[..n]);
+    /// Finish absorbing and return a reader over the output stream.
+    ///
+    /// The first [`output_len`](Self::output_len) bytes equal
+    /// [`finalize`](Self::finalize); shorter reads are prefixes of it.
+    /// Reading past `output_len` extends a digest that was bound to
+    /// that length; hashers meant for long output should be built
+    /// with [`Turb1600Builder::xof`](crate::Turb1600Builder::xof).
+    pub fn finalize_xof(self) -> Turb1600Xof {
+        Turb1600Xof {
+            reader: self.sponge.finalize_xof(),
+        }
+    }
+
+    pub(crate) fn from_sponge(sponge: Sponge) -> Self {
+        Self::from_parts(sponge, OUT_BYTES)
+    }
+
+    pub(crate) fn from_parts(sponge: Sponge, out_len: usize) -> Self {
+        Self {
+            init: sponge.clone(),
+            sponge,
+            out_len,
+            absorbed: 0,
+            progress: None,
+        }
+    }
+}
+
+impl Default for Turb1600 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Extendable output of a finalized [`Turb1600`].
+#[derive(Clone)]
+pub struct Turb1600Xof {
+    reader: XofReader,
+}
+
+/// Rate blocks staged per write by [`Turb1600Xof::squeeze_to_writer`].
+const WRITER_BLOCKS: usize = 64;
+
+impl Turb1600Xof {
+    /// Fill `out` with the next bytes of the output stream.
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        self.reader.squeeze(out);
+    }
+
+    /// Write the next `len` bytes of the output stream to `w`.
+    ///
+    /// Output is produced a few rate blocks at a time, so memory use
+    /// stays constant however large `len` is.
+    pub fn squeeze_to_writer<W: Write>(&mut self, mut len: u64, mut w: W) -> io::Result<()> {
+        let mut buf = [0u8; BLOCK_BYTES * WRITER_BLOCKS];
+        while len > 0 {
+            let n = len.min(buf.len() as u64) as usize;
+            self.squeeze(&mut buf[..n]);
+            w.write_all(&buf[..n])?;
+            len -= n as u64;
+        }
+        Ok(())
+    }
+}
+
+pub fn turb1600_hash(data: &[u8]) -> Vec<u8> {
+    let mut sponge = Sponge::new();
+    sponge.update(data);
+    sponge.finalize()
+}
+
+/// Hash the concatenation of `parts` without joining them first.
+///
+/// Equal to `turb1600_hash(&parts.concat())`: part boundaries are
+/// not encoded, so `["ab", "c"]` and `["a", "bc"]` collide by design.
+/// Use [`TurbHash`](crate::TurbHash) when boundaries must count.
+pub fn turb1600_hash_parts(parts: &[&[u8]]) -> Vec<u8> {
+    let mut sponge = Sponge::new();
+    for part in parts {
+        sponge.update(part);
+    }
+    sponge.finalize()
+}
+
+/// Hash `msg` and write `len` bytes of extendable output to `w`.
+///
+/// The first [`DIGEST_BYTES`] bytes equal [`turb1600_hash`].
+pub fn xof_to_writer<W: Write>(msg: &[u8], len: u64, w: W) -> io::Result<()> {
+    let mut hasher = Turb1600::new();
+    hasher.update(msg);
+    hasher.finalize_xof().squeeze_to_writer(len, w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Digests of `(i * 31 + 7) as u8` for i in 0..len, generated with the
+    // reference implementation in `ref/turb1600.py`. Running the suite on a
+    // big-endian target (e.g. `cargo miri test --target s390x-unknown-linux-gnu`)
+    // checks that the byte order of lane loads and stores is portable.
+    const VECTORS: &[(usize, &str)] = &[
+        (0, "9a424134b0701b747e3b5eee71e83c1398f43aa2218196c3de21174b1418795f539e670330f8e1808dccb5beecda03e49a57dca537d8d06361dc7e1c50207ea9625ecb288179e6d3e3fcfeac1c2c9757f5b4e4ec13a3685aac458f9841e286475422dc98f151f09f5033245f28cb0033b9540bd5fce500c642f499f77f4acad5"),
+        (1, "010b807722da22fef6fe82c133f968ea81e1abd25aed9d513d122b65a032adf5cf1dc4a838d015154e1fdc3c59e9c6b81e4931ebddfaafad2559607c768b7a5228cf4eebb3ea5c0583caf235eceb11c3c46af9c895de7f2a74866c2faa851ae7f876a7dc4d1d5a74fc1723c04c4542a80111c55b23541c45f4269d7872dc7bb9"),
+        (8, "0e65c66af67b743aa4bf040050d3d1f1404308d06a360fde314b44fd8b4576266b41f4d472905e0dfc5f3e1f5bf48aa540105a7a6cb48400aae8ed2764cc0b077cb42d6565c83204d5cb9a3113344afa2d084c2d9e667c195cea7ea3e66d06512155158daaab1c97d519a7c054c35fed6c547d43fee68e1b2e851567e86e7ee6"),
+        (135, "83a0e88a792faf51e4f4fa5d0dc3158d98be7fc8974370404c3abadee2771cb51674f5566416d603d94908c5461ea804b1328daf537d60b14bee0de62257848ce9309fbb8970b68c9afe9f6f58e34520798e8e032f9777d2ef65816705efad5442e9c3afdfb181d840b30ab7a2e9c6a6e7e7465c5184bc970b3fa098114d9686"),
+        (136, "f9921cf28826d0ed279ee27d6f5b62892e63e9b0d501f9f9718f65f99a42e48b166d557e9711b654c3cd8cf5a8bf7d39c60ba5874be4a97864b4641639f70428e0e73e8f40709d242e52e0809b5e823e5aa427f9b6f291e4030bf42a2870b151cb860e7cff37d5f1addd5ad7784f8dc6711c8aadaa519026d8032bbc163c074e"),
+        (137, "8b8b7823058157984a6454786e6fc00411881eda37b90ec619869db52fe91557bafd17506ec3994b653a8f9232655c31a70fb3e93f8f308203a3d1354bc020386de12b2e418c6fdd673120bc8f7eec372a060a2bf598935b067db0024a24cc421ccab827285c1a8fff011da28121fb9c35fe3240743a8151346faf29bf043ea6"),
+        (299, "a373a10a874a1b88ea69a44dccb4988e34e6493b22e6f7dbfc256969f2ac9f34158cfba68856423da5112b1269d7b485994595350ca5008772f75e34bfe9607c1feab1cf59e9996590016bf0ea5b64d92b1c94e6bb018bbf0891214384a5fe6d47917368a5aa00d1b39c240df547e73a36c1b6d79a3c8578802ce69bba5ca739"),
+        (409, "fab9c9f956ad861c9058d4dbfe9030bc72e15a504d8fa02c52214bbc59aeb22ec565e55432d64aba2af19da5dc1a592bed83605d0c775620bf782dafa8ab4adc2d9100382c28366a5708fa2ab10b9bce885ef8842c196fd4494c93f8d9cbcc55c02ac304c23159e28b79b3c49de822baa471b2de2dc99110f810b0f781999ea5"),
+    ];
+
+    fn pattern(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 + 7) as u8).collect()
+    }
+
+    #[test]
+    fn test_known_answers() {
+        for &(len, expected) in VECTORS {
+            assert_eq!(hex::encode(turb1600_hash(&pattern(len))), expected, "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_hash_parts_is_concatenation() {
+        let data: Vec<u8> = (0..700u32).map(|i| (i * 13) as u8).collect();
+        let parts: [&[u8]; 5] = [&data[..3], &[], &data[3..200], &data[200..272], &data[272..]];
+        assert_eq!(turb1600_hash_parts(&parts), turb1600_hash(&data));
+        assert_eq!(turb1600_hash_parts(&[]), turb1600_hash(b""));
+    }
+
+    #[test]
+    fn test_squeeze_to_writer_matches_squeeze() {
+        let len = BLOCK_BYTES * WRITER_BLOCKS * 2 + 77;
+        let mut expected = vec![0u8; len + 10];
+        Turb1600::new().finalize_xof().squeeze(&mut expected);
+
+        let mut out = Vec::new();
+        xof_to_writer(b"", len as u64, &mut out).unwrap();
+        assert_eq!(out, expected[..len]);
+
+        // Continues from wherever a previous squeeze stopped
+        let mut xof = Turb1600::new().finalize_xof();
+        let mut head = [0u8; 5];
+        xof.squeeze(&mut head);
+        let mut rest = Vec::new();
+        xof.squeeze_to_writer(len as u64 + 5, &mut rest).unwrap();
+        assert_eq!(rest, expected[5..len + 10]);
+    }
+
+    #[test]
+    fn test_squeeze_is_split_invariant() {
+        let mut h = Turb1600::new();
+        h.update(b"xof");
+        let mut whole = vec![0u8; 1000];
+        h.clone().finalize_xof().squeeze(&mut whole);
+
+        let mut xof = h.finalize_xof();
+        let mut parts = vec![0u8; 1000];
+        let mut off = 0;
+        for n in [5, 136, 300, 1, 135, 423] {
+            xof.squeeze(&mut parts[off..off + n]);
             off += n;
         }
+        assert_eq!(whole, parts);
+    }
 
-        permute(&mut state, &mut tmp, round);
-        round += 1;
+    #[test]
+    fn test_lanes_are_little_endian() {
+        let mut state = [0u64; LANES];
+        let mut block = [0u8; BLOCK_BYTES];
+        block[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        block[BLOCK_BYTES - 1] = 0xff;
+
+        absorb_block(&mut state, &block);
+        assert_eq!(state[0], 0x0807060504030201);
+        assert_eq!(state[BLOCK_LANES - 1], 0xff00000000000000);
     }
 
-    out
+    #[test]
+    fn test_key_bytes_do_not_stay_buffered() {
+        // Key lengths ending mid-block and on a block boundary
+        for len in [3, BLOCK_BYTES - 8, 2 * BLOCK_BYTES - 8] {
+            let key = crate::SecretKey::new(&vec![0xa5; len]);
+            let sponge = crate::mac::keyed_sponge(&key);
+            assert!(sponge.buf.iter().all(|&b| b == 0), "key length {}", len);
+        }
+    }
 }
@@ -0,0 +1,178 @@
+// =========================================================
+// turb1600 — Directory tree hashing
+//
+// One digest for a whole directory. Every entry below the root
+// (files, directories and symlinks; symlinks are not followed)
+// is encoded and absorbed under the `dir` domain tag, in order
+// of its relative path compared component by component as bytes:
+//
+//   dir    = H_dir(flags || entry_0 || … || entry_{n-1})
+//   entry  = kind || LE64(len(path)) || path || [LE32(mode)] || body
+//   body   = LE64(size) || H(contents)       (kind 0x01, file)
+//          | (empty)                         (kind 0x02, directory)
+//          | LE64(len(target)) || target     (kind 0x03, symlink)
+//
+// `path` joins the components with `/`. `flags` is 0x01 when
+// modes are included, else 0x00. Modes are the Unix permission
+// bits (`mode & 0o7777`); elsewhere directories are 0o755 and
+// files 0o644, or 0o444 when read-only. Names are raw bytes on
+// Unix and must be valid UTF-8 on other platforms. Other entry
+// kinds (sockets, devices, …) are rejected.
+// =========================================================
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::core::{Sponge, DIR_TAG};
+use crate::file::hash_file;
+
+const KIND_FILE: u8 = 0x01;
+const KIND_DIR: u8 = 0x02;
+const KIND_SYMLINK: u8 = 0x03;
+
+const FLAG_MODES: u8 = 0x01;
+
+struct Entry {
+    /// Relative path components, as encoded
+    components: Vec<Vec<u8>>,
+    path: PathBuf,
+    meta: fs::Metadata,
+}
+
+#[cfg(unix)]
+fn name_bytes(name: &std::ffi::OsStr) -> io::Result<Vec<u8>> {
+    use std::os::unix::ffi::OsStrExt;
+    Ok(name.as_bytes().to_vec())
+}
+
+#[cfg(not(unix))]
+fn name_bytes(name: &std::ffi::OsStr) -> io::Result<Vec<u8>> {
+    name.to_str()
+        .map(|s| s.as_bytes().to_vec())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "file name is not UTF-8"))
+}
+
+#[cfg(unix)]
+fn mode_bits(meta: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn mode_bits(meta: &fs::Metadata) -> u32 {
+    match (meta.is_dir(), meta.permissions().readonly()) {
+        (true, _) => 0o755,
+        (false, true) => 0o444,
+        (false, false) => 0o644,
+    }
+}
+
+fn collect(dir: &Path, prefix: &[Vec<u8>], out: &mut Vec<Entry>) -> io::Result<()> {
+    for item in fs::read_dir(dir)? {
+        let item = item?;
+        let path = item.path();
+        let meta = fs::symlink_metadata(&path)?;
+
+        let mut components = prefix.to_vec();
+        components.push(name_bytes(&item.file_name())?);
+
+        if meta.is_dir() {
+            collect(&path, &components, out)?;
+        }
+        out.push(Entry { components, path, meta });
+    }
+    Ok(())
+}
+
+fn hash_tree(root: &Path, modes: bool) -> io::Result<Vec<u8>> {
+    if !fs::metadata(root)?.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a directory"));
+    }
+
+    let mut entries = Vec::new();
+    collect(root, &[], &mut entries)?;
+    entries.sort_by(|a, b| a.components.cmp(&b.components));
+
+    let mut sponge = Sponge::with_tag(DIR_TAG);
+    sponge.update(&[if modes { FLAG_MODES } else { 0 }]);
+
+    for entry in &entries {
+        let kind = entry.meta.file_type();
+        let kind = if kind.is_file() {
+            KIND_FILE
+        } else if kind.is_dir() {
+            KIND_DIR
+        } else if kind.is_symlink() {
+            KIND_SYMLINK
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{}: unsupported file type", entry.path.display()),
+            ));
+        };
+
+        let path = entry.components.join(&b'/');
+        sponge.update(&[kind]);
+        sponge.update(&(path.len() as u64).to_le_bytes());
+        sponge.update(&path);
+        if modes {
+            sponge.update(&mode_bits(&entry.meta).to_le_bytes());
+        }
+
+        match kind {
+            KIND_FILE => {
+                sponge.update(&entry.meta.len().to_le_bytes());
+                sponge.update(&hash_file(&entry.path)?);
+            }
+            KIND_SYMLINK => {
+                let target = name_bytes(fs::read_link(&entry.path)?.as_os_str())?;
+                sponge.update(&(target.len() as u64).to_le_bytes());
+                sponge.update(&target);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(sponge.finalize())
+}
+
+/// Hash a directory tree: contents, relative paths and entry kinds.
+pub fn hash_dir<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    hash_tree(path.as_ref(), false)
+}
+
+/// Like [`hash_dir`], also covering each entry's permission mode.
+pub fn hash_dir_with_modes<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    hash_tree(path.as_ref(), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn test_hash_dir_covers_paths_and_contents() {
+        let root = TestDir::new("dir");
+        fs::create_dir_all(root.join("sub/empty")).unwrap();
+        fs::write(root.join("a.txt"), b"alpha").unwrap();
+        fs::write(root.join("sub/b.txt"), b"beta").unwrap();
+
+        let first = hash_dir(&root).unwrap();
+        assert_eq!(hash_dir(&root).unwrap(), first);
+        assert_ne!(hash_dir_with_modes(&root).unwrap(), first);
+
+        fs::write(root.join("sub/b.txt"), b"betA").unwrap();
+        let changed = hash_dir(&root).unwrap();
+        assert_ne!(changed, first);
+
+        fs::rename(root.join("sub/b.txt"), root.join("sub/c.txt")).unwrap();
+        let renamed = hash_dir(&root).unwrap();
+        assert_ne!(renamed, changed);
+
+        fs::remove_dir(root.join("sub/empty")).unwrap();
+        let pruned = hash_dir(&root).unwrap();
+        assert_ne!(pruned, renamed);
+    }
+}
@@ -0,0 +1,175 @@
+// =========================================================
+// turb1600 — Error type
+//
+// `Turb1600Error` covers every way a fallible library call can
+// fail. Module errors (`BuildError`, `MultiformatError`, …) keep
+// their precise variants and convert into it with `?`, so callers
+// mixing several APIs need only one error type. IO-only helpers
+// still return `io::Result`; the conversion back to `io::Error`
+// lets readers such as `VerifyingReader` report digest failures
+// that callers can downcast.
+//
+// Infallible-looking calls that panic on a bad argument have a
+// `try_` twin returning this type instead:
+//
+//   derive_key            try_derive_key          ZeroIterations
+//   turb1600_tree_hash    try_turb1600_tree_hash  ZeroChunkSize
+//   SegmentHasher::new    SegmentHasher::try_new  ZeroSegmentSize
+//   finalize_into         try_finalize_into       DigestLength
+// =========================================================
+
+use std::fmt;
+use std::io;
+
+use crate::builder::BuildError;
+#[cfg(feature = "gpu")]
+use crate::gpu::GpuError;
+use crate::multiformats::MultiformatError;
+
+/// Any error returned by the turb1600 library.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Turb1600Error {
+    /// Reading or writing failed.
+    Io(io::Error),
+    /// A [`Turb1600Builder`](crate::Turb1600Builder) configuration was rejected.
+    Build(BuildError),
+    /// Malformed multihash or multibase input.
+    Multiformat(MultiformatError),
+    /// The GPU backend failed.
+    #[cfg(feature = "gpu")]
+    Gpu(GpuError),
+    /// A digest string is not valid hex.
+    InvalidHex,
+    /// A digest has the wrong number of bytes.
+    DigestLength { expected: usize, actual: usize },
+    /// Data does not hash to the expected digest.
+    DigestMismatch,
+    /// A key derivation was asked for zero iterations.
+    ZeroIterations,
+    /// A tree hash was asked for zero-byte chunks.
+    ZeroChunkSize,
+    /// A segment hasher was asked for zero-byte segments.
+    ZeroSegmentSize,
+}
+
+impl fmt::Display for Turb1600Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Turb1600Error::Io(e) => e.fmt(f),
+            Turb1600Error::Build(e) => e.fmt(f),
+            Turb1600Error::Multiformat(e) => e.fmt(f),
+            #[cfg(feature = "gpu")]
+            Turb1600Error::Gpu(e) => e.fmt(f),
+            Turb1600Error::InvalidHex => f.write_str("invalid hex digest"),
+            Turb1600Error::DigestLength { expected, actual } => {
+                write!(f, "digest is {} bytes, expected {}", actual, expected)
+            }
+            Turb1600Error::DigestMismatch => f.write_str("turb1600 digest mismatch"),
+            Turb1600Error::ZeroIterations => f.write_str("kdf iteration count must be non-zero"),
+            Turb1600Error::ZeroChunkSize => f.write_str("tree chunk size must be non-zero"),
+            Turb1600Error::ZeroSegmentSize => f.write_str("segment size must be non-zero"),
+        }
+    }
+}
+
+impl std::error::Error for Turb1600Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Turb1600Error::Io(e) => Some(e),
+            Turb1600Error::Build(e) => Some(e),
+            Turb1600Error::Multiformat(e) => Some(e),
+            #[cfg(feature = "gpu")]
+            Turb1600Error::Gpu(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Turb1600Error {
+    fn from(e: io::Error) -> Self {
+        Turb1600Error::Io(e)
+    }
+}
+
+impl From<BuildError> for Turb1600Error {
+    fn from(e: BuildError) -> Self {
+        Turb1600Error::Build(e)
+    }
+}
+
+impl From<MultiformatError> for Turb1600Error {
+    fn from(e: MultiformatError) -> Self {
+        Turb1600Error::Multiformat(e)
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl From<GpuError> for Turb1600Error {
+    fn from(e: GpuError) -> Self {
+        Turb1600Error::Gpu(e)
+    }
+}
+
+/// IO errors pass through unchanged; anything else becomes
+/// `InvalidData` carrying the original error.
+impl From<Turb1600Error> for io::Error {
+    fn from(e: Turb1600Error) -> Self {
+        match e {
+            Turb1600Error::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions_keep_the_cause() {
+        let e: Turb1600Error = BuildError::EmptyKey.into();
+        assert!(matches!(e, Turb1600Error::Build(BuildError::EmptyKey)));
+        assert_eq!(e.to_string(), "key must not be empty");
+
+        let io_err = io::Error::from(Turb1600Error::DigestMismatch);
+        assert_eq!(io_err.kind(), io::ErrorKind::InvalidData);
+        let inner = io_err.get_ref().and_then(|e| e.downcast_ref::<Turb1600Error>());
+        assert!(matches!(inner, Some(Turb1600Error::DigestMismatch)));
+
+        let io_err = io::Error::from(Turb1600Error::Io(io::ErrorKind::NotFound.into()));
+        assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_try_variants_return_errors() {
+        assert!(matches!(
+            crate::try_derive_key(b"pass", b"salt", 0, 32),
+            Err(Turb1600Error::ZeroIterations)
+        ));
+        assert_eq!(
+            crate::try_derive_key(b"pass", b"salt", 2, 32).unwrap(),
+            crate::derive_key(b"pass", b"salt", 2, 32)
+        );
+        assert!(matches!(
+            crate::try_turb1600_tree_hash(b"data", 0),
+            Err(Turb1600Error::ZeroChunkSize)
+        ));
+        assert!(matches!(
+            crate::SegmentHasher::try_new(0),
+            Err(Turb1600Error::ZeroSegmentSize)
+        ));
+
+        let mut out = [0u8; 16];
+        assert!(matches!(
+            crate::Turb1600::new().try_finalize_into(&mut out),
+            Err(Turb1600Error::DigestLength { expected: 128, actual: 16 })
+        ));
+        let mut out = [0u8; 128];
+        crate::Turb1600::new().try_finalize_into(&mut out).unwrap();
+        assert_eq!(out.to_vec(), crate::turb1600_hash(b""));
+
+        let err = crate::turb1600_tree_hash_reader(&b"data"[..], 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
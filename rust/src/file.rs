@@ -0,0 +1,360 @@
+// =========================================================
+// turb1600 — File hashing
+// =========================================================
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::core::Turb1600;
+
+/// Read buffer size used by the file helpers.
+pub const FILE_BUFFER_DEFAULT: usize = 1 << 20;
+
+/// Buffer used by [`Turb1600::update_reader`].
+const UPDATE_READER_BUFFER: usize = 64 << 10;
+
+/// Absorb `reader` to EOF through `buf`; returns the bytes read.
+fn update_from<R: Read>(hasher: &mut Turb1600, mut reader: R, buf: &mut [u8]) -> io::Result<u64> {
+    let mut total = 0u64;
+    loop {
+        match reader.read(buf) {
+            Ok(0) => return Ok(total),
+            Ok(n) => {
+                hasher.update(&buf[..n]);
+                total += n as u64;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+impl Turb1600 {
+    /// Absorb everything `reader` yields, returning the byte count.
+    ///
+    /// Equivalent to `update` with the same bytes, so slice and
+    /// reader updates can be mixed freely on one hasher.
+    pub fn update_reader<R: Read>(&mut self, reader: R) -> io::Result<u64> {
+        update_from(self, reader, &mut vec![0u8; UPDATE_READER_BUFFER])
+    }
+}
+
+/// Hash everything `reader` yields, using a `buf_size`-byte buffer.
+pub fn hash_reader<R: Read>(reader: R, buf_size: usize) -> io::Result<Vec<u8>> {
+    let mut hasher = Turb1600::new();
+    update_from(&mut hasher, reader, &mut vec![0u8; buf_size.max(1)])?;
+    Ok(hasher.finalize())
+}
+
+/// Hash a file on Linux with reads overlapped against hashing.
+///
+/// A reader thread fills one buffer while the calling thread hashes
+/// the previous one. Each read is first tried as
+/// `preadv2(RWF_NOWAIT)`, which returns at once when the data is
+/// already cached; on a miss the thread queues `readahead(2)` for
+/// the following window and then blocks on the read. Anything that
+/// is not a regular file is read normally.
+#[cfg(all(target_os = "linux", feature = "readahead"))]
+pub fn hash_file_readahead<P: AsRef<Path>>(path: P, buf_size: usize) -> io::Result<Vec<u8>> {
+    let mut hasher = Turb1600::new();
+    update_readahead(&mut hasher, File::open(path)?, buf_size)?;
+    Ok(hasher.finalize())
+}
+
+/// Read into `buf` at `offset`, without blocking when the page cache
+/// already holds the data. `nowait` is cleared for good once the
+/// kernel or filesystem rejects `RWF_NOWAIT`.
+#[cfg(all(target_os = "linux", feature = "readahead"))]
+fn read_window(file: &File, buf: &mut [u8], offset: u64, nowait: &mut bool) -> io::Result<usize> {
+    use std::os::unix::fs::FileExt;
+    use std::os::unix::io::AsRawFd;
+
+    if *nowait {
+        let off = libc::off_t::try_from(offset)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file offset out of range"))?;
+        let iov = libc::iovec {
+            iov_base: buf.as_mut_ptr().cast(),
+            iov_len: buf.len(),
+        };
+        // SAFETY: `iov` describes `buf`, which is writable for its whole
+        // length, and the descriptor is owned by `file`.
+        let n = unsafe { libc::preadv2(file.as_raw_fd(), &iov, 1, off, libc::RWF_NOWAIT) };
+        if n > 0 {
+            return Ok(n as usize);
+        }
+        if n < 0 {
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::EAGAIN) | Some(libc::EINTR) => {}
+                Some(libc::EOPNOTSUPP) | Some(libc::ENOSYS) | Some(libc::EINVAL) => *nowait = false,
+                _ => return Err(err),
+            }
+        }
+    }
+
+    // A miss (or EOF): have the window after this one fetched while
+    // the kernel serves this read.
+    if let Ok(next) = (offset + buf.len() as u64).try_into() {
+        // SAFETY: the descriptor is owned by `file`. The hint is
+        // advisory; errors are ignored.
+        unsafe {
+            libc::readahead(file.as_raw_fd(), next, buf.len());
+        }
+    }
+    loop {
+        match file.read_at(buf, offset) {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "readahead"))]
+fn update_readahead(hasher: &mut Turb1600, file: File, buf_size: usize) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    use std::sync::mpsc;
+
+    let buf_size = buf_size.max(1);
+    if !file.metadata()?.is_file() {
+        return update_from(hasher, file, &mut vec![0u8; buf_size]).map(|_| ());
+    }
+    // SAFETY: the descriptor is owned by `file`. The hint is advisory;
+    // errors are ignored.
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_SEQUENTIAL);
+    }
+
+    let (full_tx, full_rx) = mpsc::sync_channel::<(Vec<u8>, usize)>(1);
+    let (empty_tx, empty_rx) = mpsc::sync_channel::<Vec<u8>>(2);
+    for _ in 0..2 {
+        empty_tx.send(vec![0u8; buf_size]).expect("buffer pool");
+    }
+
+    std::thread::scope(|scope| {
+        let reader = scope.spawn(move || -> io::Result<()> {
+            let (mut offset, mut nowait) = (0u64, true);
+            while let Ok(mut buf) = empty_rx.recv() {
+                let n = read_window(&file, &mut buf, offset, &mut nowait)?;
+                offset += n as u64;
+                if n == 0 || full_tx.send((buf, n)).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        for (buf, n) in full_rx {
+            hasher.update(&buf[..n]);
+            // The reader may already have finished; a closed pool is fine.
+            let _ = empty_tx.send(buf);
+        }
+
+        reader.join().expect("reader thread panicked")
+    })
+}
+
+/// Bytes mapped and hashed at a time by [`hash_file_mmap`].
+#[cfg(all(unix, feature = "mmap"))]
+pub const MMAP_WINDOW: usize = 64 << 20;
+
+/// Hash a file by memory-mapping it instead of reading it.
+///
+/// The file is mapped [`MMAP_WINDOW`] bytes at a time with
+/// sequential and will-need hints, and each window is unmapped once
+/// hashed, so address space and resident memory stay bounded even
+/// on 32-bit targets. Saves the copy into a read buffer, which
+/// helps backup tools hashing large files already in the page
+/// cache. Anything that is not a regular file is read normally.
+///
+/// The file must not be truncated while it is hashed: touching a
+/// mapped page past the new end raises `SIGBUS`. Concurrent writes
+/// give a digest of neither version.
+#[cfg(all(unix, feature = "mmap"))]
+pub fn hash_file_mmap<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    let mut hasher = Turb1600::new();
+    update_mmap(&mut hasher, File::open(path)?, MMAP_WINDOW)?;
+    Ok(hasher.finalize())
+}
+
+/// `window` must be a multiple of the page size.
+#[cfg(all(unix, feature = "mmap"))]
+fn update_mmap(hasher: &mut Turb1600, file: File, window: usize) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let meta = file.metadata()?;
+    if !meta.is_file() {
+        return update_from(hasher, file, &mut vec![0u8; FILE_BUFFER_DEFAULT]).map(|_| ());
+    }
+
+    let len = meta.len();
+    let mut offset = 0u64;
+    while offset < len {
+        let size = (len - offset).min(window as u64) as usize;
+        let off = libc::off_t::try_from(offset)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file offset out of range"))?;
+
+        // SAFETY: a read-only private mapping of `size` bytes of an open
+        // file at a page-aligned offset. It is unmapped below before
+        // the next window.
+        let ptr = unsafe {
+            libc::mmap(std::ptr::null_mut(), size, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), off)
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: `ptr` is a live mapping of `size` readable bytes. The
+        // hints are advisory; errors are ignored.
+        unsafe {
+            libc::madvise(ptr, size, libc::MADV_SEQUENTIAL);
+            libc::madvise(ptr, size, libc::MADV_WILLNEED);
+            hasher.update(std::slice::from_raw_parts(ptr as *const u8, size));
+            libc::munmap(ptr, size);
+        }
+        offset += size as u64;
+    }
+    Ok(())
+}
+
+/// Absorb a whole file, through the readahead backend where available.
+fn update_file(hasher: &mut Turb1600, path: &Path, buf_size: usize) -> io::Result<()> {
+    let file = File::open(path)?;
+
+    #[cfg(all(target_os = "linux", feature = "readahead"))]
+    {
+        update_readahead(hasher, file, buf_size)
+    }
+
+    #[cfg(not(all(target_os = "linux", feature = "readahead")))]
+    {
+        update_from(hasher, file, &mut vec![0u8; buf_size.max(1)]).map(|_| ())
+    }
+}
+
+/// Hash a file, using the readahead backend where available.
+pub fn hash_file<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    hash_file_buffered(path, FILE_BUFFER_DEFAULT)
+}
+
+/// Like [`hash_file`], streaming through `buf_size`-byte buffers, so
+/// memory use is bounded regardless of the file's size.
+pub fn hash_file_buffered<P: AsRef<Path>>(path: P, buf_size: usize) -> io::Result<Vec<u8>> {
+    let mut hasher = Turb1600::new();
+    update_file(&mut hasher, path.as_ref(), buf_size)?;
+    Ok(hasher.finalize())
+}
+
+/// Like [`hash_file`], calling `on_progress` with the bytes hashed
+/// so far after each buffer (see [`Turb1600::on_progress`]).
+pub fn hash_file_with_progress<P, F>(path: P, on_progress: F) -> io::Result<Vec<u8>>
+where
+    P: AsRef<Path>,
+    F: Fn(u64) + Send + Sync + 'static,
+{
+    let mut hasher = Turb1600::new().on_progress(on_progress);
+    update_file(&mut hasher, path.as_ref(), FILE_BUFFER_DEFAULT)?;
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdir::TestDir;
+
+    #[test]
+    fn test_hash_reader_small_buffer() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i * 7) as u8).collect();
+        assert_eq!(hash_reader(&data[..], 100).unwrap(), crate::turb1600_hash(&data));
+    }
+
+    #[test]
+    fn test_update_reader_mixes_with_update() {
+        let body: Vec<u8> = (0..100_000u32).map(|i| (i * 3) as u8).collect();
+
+        let mut hasher = Turb1600::new();
+        hasher.update(b"header:");
+        assert_eq!(hasher.update_reader(&body[..]).unwrap(), body.len() as u64);
+        hasher.update(b":trailer");
+
+        let joined = [&b"header:"[..], &body, b":trailer"].concat();
+        assert_eq!(hasher.finalize(), crate::turb1600_hash(&joined));
+    }
+
+    #[cfg(all(unix, feature = "mmap"))]
+    #[test]
+    fn test_hash_file_mmap_matches_hash_file() {
+        let dir = TestDir::new("mmap");
+        let path = dir.join("data");
+        for len in [0, 1000, 200_000] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 13) as u8).collect();
+            std::fs::write(&path, &data).unwrap();
+            assert_eq!(hash_file_mmap(&path).unwrap(), crate::turb1600_hash(&data), "len {}", len);
+
+            let mut hasher = Turb1600::new();
+            update_mmap(&mut hasher, File::open(&path).unwrap(), 64 << 10).unwrap();
+            assert_eq!(hasher.finalize(), crate::turb1600_hash(&data), "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_progress_reports_running_total() {
+        use std::sync::{Arc, Mutex};
+
+        let data: Vec<u8> = (0..3 * FILE_BUFFER_DEFAULT as u32 / 2).map(|i| i as u8).collect();
+        let dir = TestDir::new("progress");
+        let path = dir.join("data");
+        std::fs::write(&path, &data).unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let digest = hash_file_with_progress(&path, move |n| log.lock().unwrap().push(n));
+
+        assert_eq!(digest.unwrap(), crate::turb1600_hash(&data));
+        let seen = seen.lock().unwrap();
+        assert!(seen.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(seen.last(), Some(&(data.len() as u64)));
+    }
+
+    #[cfg(all(target_os = "linux", feature = "readahead"))]
+    #[test]
+    fn test_readahead_matches_one_shot() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i * 13) as u8).collect();
+        let dir = TestDir::new("readahead");
+        let path = dir.join("data");
+        std::fs::write(&path, &data).unwrap();
+        let digest = hash_file_readahead(&path, 4096);
+
+        // Both read paths, cached and blocking, land on the same bytes
+        let file = File::open(&path).unwrap();
+        let mut buf = vec![0u8; 1000];
+        for nowait in [true, false] {
+            let mut nowait = nowait;
+            let n = read_window(&file, &mut buf, 5000, &mut nowait).unwrap();
+            assert!(n > 0);
+            assert_eq!(buf[..n], data[5000..5000 + n]);
+        }
+        assert_eq!(digest.unwrap(), crate::turb1600_hash(&data));
+    }
+
+    #[cfg(all(target_os = "linux", feature = "readahead"))]
+    #[test]
+    fn test_readahead_reads_pipes() {
+        use std::io::Write;
+        use std::os::unix::io::FromRawFd;
+
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i * 7) as u8).collect();
+        let mut fds = [0; 2];
+        // SAFETY: `fds` has room for the two descriptors pipe(2) returns.
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        // SAFETY: both descriptors were just created and are owned here.
+        let (rx, mut tx) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+
+        let body = data.clone();
+        let writer = std::thread::spawn(move || tx.write_all(&body));
+        let mut hasher = Turb1600::new();
+        update_readahead(&mut hasher, rx, 4096).unwrap();
+        writer.join().unwrap().unwrap();
+        assert_eq!(hasher.finalize(), crate::turb1600_hash(&data));
+    }
+}
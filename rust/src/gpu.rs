@@ -0,0 +1,463 @@
+// =========================================================
+// turb1600 — Experimental GPU batch hashing (wgpu)
+//
+// Each shader invocation hashes one message. Messages are
+// padded into rate blocks on the CPU; the IV and the round
+// constants are also computed on the CPU and uploaded, so the
+// shader only runs absorb / permute / squeeze. WGSL has no
+// 64-bit integers, so lanes are vec2<u32> (x = low, y = high).
+// The permutation is generated from the core tables.
+//
+// Every buffer must fit the device's storage binding limit. The
+// constants grow by ROUNDS_MAIN lanes (288 bytes) per block of the
+// longest message, more than the block itself, so they bound the
+// message length; the block and output buffers bound how many
+// messages go into one dispatch.
+// =========================================================
+
+use std::borrow::Cow;
+use std::fmt;
+use std::fmt::Write as _;
+
+use wgpu::util::DeviceExt;
+
+use crate::core::{
+    initial_state, round_constant, BLOCK_BYTES, BLOCK_LANES, DIGEST_BYTES, LANES, PERM_TABLE,
+    ROT_TABLE, ROUNDS_FINAL, ROUNDS_MAIN,
+};
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_WORKGROUPS: usize = 65_535;
+
+/// Failure to set up or run the GPU backend.
+#[derive(Debug)]
+pub enum GpuError {
+    /// No compatible adapter was found.
+    NoAdapter,
+    /// The adapter refused to create a device.
+    Device(wgpu::RequestDeviceError),
+    /// Reading results back from the GPU failed.
+    Readback(wgpu::BufferAsyncError),
+    /// A message is longer than the device's buffers can hold.
+    MessageTooLarge { len: usize, max: usize },
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuError::NoAdapter => f.write_str("no compatible GPU adapter found"),
+            GpuError::Device(e) => write!(f, "failed to create GPU device: {}", e),
+            GpuError::Readback(e) => write!(f, "failed to read GPU results: {}", e),
+            GpuError::MessageTooLarge { len, max } => {
+                write!(f, "message of {} bytes exceeds the GPU limit of {} bytes", len, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+/// Build the WGSL source with the permutation unrolled from the
+/// core rotation and permutation tables.
+fn shader_source() -> String {
+    let mut rho_pi = String::new();
+    for i in 0..LANES {
+        let _ = writeln!(
+            rho_pi,
+            "    t[{}] = rotl(s[{}], {}u + ro);",
+            PERM_TABLE[i], i, ROT_TABLE[i]
+        );
+    }
+
+    format!(
+        r#"
+struct Params {{
+    count: u32,
+    _pad0: u32,
+    _pad1: u32,
+    _pad2: u32,
+}}
+
+@group(0) @binding(0) var<uniform> params: Params;
+// IV lanes followed by the round constants
+@group(0) @binding(1) var<storage, read> consts: array<vec2<u32>>;
+@group(0) @binding(2) var<storage, read> blocks: array<u32>;
+@group(0) @binding(3) var<storage, read> info: array<vec2<u32>>;
+@group(0) @binding(4) var<storage, read_write> out: array<u32>;
+
+var<private> s: array<vec2<u32>, {lanes}>;
+var<private> t: array<vec2<u32>, {lanes}>;
+
+fn rotl(x: vec2<u32>, r: u32) -> vec2<u32> {{
+    let n = r & 63u;
+    var v = x;
+    if (n >= 32u) {{
+        v = vec2<u32>(x.y, x.x);
+    }}
+    let m = n & 31u;
+    if (m == 0u) {{
+        return v;
+    }}
+    return vec2<u32>((v.x << m) | (v.y >> (32u - m)), (v.y << m) | (v.x >> (32u - m)));
+}}
+
+fn permute(round: u32) {{
+    var c: array<vec2<u32>, 5>;
+    for (var x = 0u; x < 5u; x++) {{
+        c[x] = s[x] ^ s[x + 5u] ^ s[x + 10u] ^ s[x + 15u] ^ s[x + 20u];
+    }}
+    var d: array<vec2<u32>, 5>;
+    d[0] = c[4] ^ rotl(c[1], 1u);
+    d[1] = c[0] ^ rotl(c[2], 1u);
+    d[2] = c[1] ^ rotl(c[3], 1u);
+    d[3] = c[2] ^ rotl(c[4], 1u);
+    d[4] = c[3] ^ rotl(c[0], 1u);
+    for (var i = 0u; i < {lanes}u; i++) {{
+        s[i] = s[i] ^ d[i % 5u];
+    }}
+
+    let ro = (round * 13u) & 63u;
+{rho_pi}
+    for (var i = 0u; i < {lanes}u; i += 5u) {{
+        let a = t[i];
+        let b = t[i + 1u];
+        let c2 = t[i + 2u];
+        let d2 = t[i + 3u];
+        let e = t[i + 4u];
+        s[i] = a ^ (~b & c2);
+        s[i + 1u] = b ^ (~c2 & d2);
+        s[i + 2u] = c2 ^ (~d2 & e);
+        s[i + 3u] = d2 ^ (~e & a);
+        s[i + 4u] = e ^ (~a & b);
+    }}
+
+    let lane = (round * 7u) % {lanes}u;
+    s[lane] = s[lane] ^ consts[{lanes}u + round];
+}}
+
+@compute @workgroup_size({wg})
+fn main(@builtin(global_invocation_id) gid: vec3<u32>) {{
+    let msg = gid.x;
+    if (msg >= params.count) {{
+        return;
+    }}
+
+    for (var i = 0u; i < {lanes}u; i++) {{
+        s[i] = consts[i];
+    }}
+
+    let first = info[msg].x;
+    let n = info[msg].y;
+    var round = 0u;
+
+    for (var b = 0u; b < n; b++) {{
+        let base = (first + b) * {block_words}u;
+        for (var i = 0u; i < {block_lanes}u; i++) {{
+            s[i] = s[i] ^ vec2<u32>(blocks[base + 2u * i], blocks[base + 2u * i + 1u]);
+        }}
+
+        var r = {rounds_main}u;
+        if (b + 1u == n) {{
+            r = {rounds_last}u;
+        }}
+        for (var k = 0u; k < r; k++) {{
+            permute(round);
+            round++;
+        }}
+    }}
+
+    s[{last_lane}] = ~s[{last_lane}];
+    for (var i = 0u; i < {out_lanes}u; i++) {{
+        out[msg * {out_words}u + 2u * i] = s[i].x;
+        out[msg * {out_words}u + 2u * i + 1u] = s[i].y;
+    }}
+}}
+"#,
+        lanes = LANES,
+        wg = WORKGROUP_SIZE,
+        rho_pi = rho_pi,
+        block_words = BLOCK_BYTES / 4,
+        block_lanes = BLOCK_LANES,
+        rounds_main = ROUNDS_MAIN,
+        rounds_last = ROUNDS_MAIN + ROUNDS_FINAL,
+        last_lane = LANES - 1,
+        out_lanes = DIGEST_BYTES / 8,
+        out_words = DIGEST_BYTES / 4,
+    )
+}
+
+/// Per-dispatch limits for a storage binding limit of `binding` bytes
+#[derive(Debug, PartialEq, Eq)]
+struct BatchLimits {
+    /// Padded blocks in the longest message (consts buffer)
+    message_blocks: usize,
+    /// Padded blocks of all messages together (blocks buffer)
+    total_blocks: usize,
+    /// Messages per dispatch (workgroups and output buffer)
+    messages: usize,
+}
+
+impl BatchLimits {
+    fn new(binding: usize) -> Self {
+        Self {
+            message_blocks: (binding / 8).saturating_sub(LANES + ROUNDS_FINAL) / ROUNDS_MAIN,
+            total_blocks: binding / BLOCK_BYTES,
+            messages: (MAX_WORKGROUPS * WORKGROUP_SIZE as usize).min(binding / DIGEST_BYTES),
+        }
+    }
+
+    /// Longest message that fits, in bytes
+    fn max_len(&self) -> usize {
+        (self.message_blocks * BLOCK_BYTES).saturating_sub(1)
+    }
+}
+
+/// Padded rate blocks of a message
+fn block_count(msg: &[u8]) -> usize {
+    msg.len() / BLOCK_BYTES + 1
+}
+
+fn lanes_to_bytes(lanes: &[u64]) -> Vec<u8> {
+    lanes.iter().flat_map(|l| l.to_le_bytes()).collect()
+}
+
+/// A GPU device and compiled pipeline, reusable across batches.
+pub struct GpuHasher {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuHasher {
+    /// Pick the default adapter and compile the hashing pipeline.
+    pub fn new() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(
+            instance.request_adapter(&wgpu::RequestAdapterOptions::default()),
+        )
+        .ok_or(GpuError::NoAdapter)?;
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("turb1600"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_defaults()
+                    .using_resolution(adapter.limits()),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        ))
+        .map_err(GpuError::Device)?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("turb1600"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(shader_source())),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("turb1600"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+        })
+    }
+
+    /// Hash every message, returning digests in input order.
+    ///
+    /// Output is identical to calling `turb1600_hash` on each message.
+    /// Fails with [`GpuError::MessageTooLarge`] before any work is
+    /// submitted if a message cannot fit the device's buffers.
+    pub fn hash_batch(&self, messages: &[&[u8]]) -> Result<Vec<Vec<u8>>, GpuError> {
+        let limits = BatchLimits::new(self.device.limits().max_storage_buffer_binding_size as usize);
+        if let Some(msg) = messages.iter().find(|msg| block_count(msg) > limits.message_blocks) {
+            return Err(GpuError::MessageTooLarge {
+                len: msg.len(),
+                max: limits.max_len(),
+            });
+        }
+
+        let mut digests = Vec::with_capacity(messages.len());
+        let mut start = 0;
+        while start < messages.len() {
+            // Fill a dispatch up to the workgroup and buffer limits. A
+            // single message always fits: its consts outgrow its blocks.
+            let mut end = start;
+            let mut blocks = 0;
+            while end < messages.len() && end - start < limits.messages {
+                let n = block_count(messages[end]);
+                if end > start && blocks + n > limits.total_blocks {
+                    break;
+                }
+                blocks += n;
+                end += 1;
+            }
+
+            digests.extend(self.dispatch(&messages[start..end], blocks)?);
+            start = end;
+        }
+
+        Ok(digests)
+    }
+
+    fn dispatch(&self, messages: &[&[u8]], total_blocks: usize) -> Result<Vec<Vec<u8>>, GpuError> {
+        let mut blocks = vec![0u8; total_blocks * BLOCK_BYTES];
+        let mut info = Vec::with_capacity(messages.len() * 8);
+        let mut max_blocks = 0;
+        let mut first = 0;
+
+        for msg in messages {
+            let n = block_count(msg);
+            let region = &mut blocks[first * BLOCK_BYTES..(first + n) * BLOCK_BYTES];
+            region[..msg.len()].copy_from_slice(msg);
+            region[msg.len()] = 0x01;
+            region[n * BLOCK_BYTES - 1] |= 0x80;
+
+            info.extend_from_slice(&(first as u32).to_le_bytes());
+            info.extend_from_slice(&(n as u32).to_le_bytes());
+            max_blocks = max_blocks.max(n);
+            first += n;
+        }
+
+        let rounds = max_blocks * ROUNDS_MAIN + ROUNDS_FINAL;
+        let mut consts = initial_state().to_vec();
+        consts.extend((0..rounds).map(round_constant));
+
+        let mut params = [0u8; 16];
+        params[..4].copy_from_slice(&(messages.len() as u32).to_le_bytes());
+
+        let storage = |label, contents: &[u8]| {
+            self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage: wgpu::BufferUsages::STORAGE,
+            })
+        };
+
+        let params = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("turb1600 params"),
+            contents: &params,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let consts = storage("turb1600 consts", &lanes_to_bytes(&consts));
+        let blocks = storage("turb1600 blocks", &blocks);
+        let info = storage("turb1600 info", &info);
+
+        let out_size = (messages.len() * DIGEST_BYTES) as u64;
+        let out = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("turb1600 out"),
+            size: out_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("turb1600 staging"),
+            size: out_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("turb1600"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: consts.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: blocks.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: info.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 4, resource: out.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("turb1600") });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("turb1600"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            let n = messages.len() as u32;
+            pass.dispatch_workgroups(n / WORKGROUP_SIZE + u32::from(n % WORKGROUP_SIZE != 0), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&out, 0, &staging, 0, out_size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = staging.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |r| {
+            let _ = tx.send(r);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        rx.recv()
+            .expect("map_async callback dropped")
+            .map_err(GpuError::Readback)?;
+
+        let digests = slice
+            .get_mapped_range()
+            .chunks_exact(DIGEST_BYTES)
+            .map(<[u8]>::to_vec)
+            .collect();
+        staging.unmap();
+
+        Ok(digests)
+    }
+}
+
+/// Hash a batch of messages on the default GPU.
+///
+/// Creates a fresh device and pipeline; keep a [`GpuHasher`] around
+/// when hashing more than one batch.
+pub fn hash_batch_gpu(messages: &[&[u8]]) -> Result<Vec<Vec<u8>>, GpuError> {
+    GpuHasher::new()?.hash_batch(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_limits_fit_binding() {
+        for binding in [1 << 16, 128 << 20, (1 << 30) + 12_345] {
+            let limits = BatchLimits::new(binding);
+            let consts = (LANES + limits.message_blocks * ROUNDS_MAIN + ROUNDS_FINAL) * 8;
+            assert!(consts <= binding);
+            assert!(consts + ROUNDS_MAIN * 8 > binding);
+            assert!(limits.message_blocks <= limits.total_blocks);
+            assert!(limits.messages * DIGEST_BYTES <= binding);
+            assert_eq!(block_count(&vec![0; limits.max_len()]), limits.message_blocks);
+        }
+        assert_eq!(BatchLimits::new(100).max_len(), 0);
+    }
+
+    #[test]
+    #[ignore = "needs a GPU adapter"]
+    fn test_gpu_matches_cpu() {
+        let gpu = GpuHasher::new().unwrap();
+
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 31 + 7) as u8).collect();
+        let messages: Vec<&[u8]> = (0..300).step_by(7).map(|n| &data[..n]).chain([&data[..]]).collect();
+
+        let digests = gpu.hash_batch(&messages).unwrap();
+        assert_eq!(digests.len(), messages.len());
+        for (msg, digest) in messages.iter().zip(&digests) {
+            assert_eq!(digest, &crate::turb1600_hash(msg));
+        }
+
+        let limits = BatchLimits::new(gpu.device.limits().max_storage_buffer_binding_size as usize);
+        let huge = vec![0u8; limits.max_len() + 1];
+        assert!(matches!(
+            gpu.hash_batch(&[b"ok", &huge]),
+            Err(GpuError::MessageTooLarge { .. })
+        ));
+    }
+}
@@ -0,0 +1,78 @@
+// =========================================================
+// turb1600 — Lowercase hex encoding
+//
+// Digests are printed as hex far more often than they are
+// hashed in bulk, so formatting one should cost a single
+// allocation at most. Each byte is looked up in a 256-entry
+// table of digit pairs and copied into the output, with no
+// per-byte formatting or branching.
+// =========================================================
+
+const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+const fn pair_table() -> [[u8; 2]; 256] {
+    let mut table = [[0u8; 2]; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = [DIGITS[i >> 4], DIGITS[i & 0xf]];
+        i += 1;
+    }
+    table
+}
+
+static PAIRS: [[u8; 2]; 256] = pair_table();
+
+/// Write the hex form of `bytes` into `out` without allocating and
+/// return it as a `&str`.
+///
+/// Panics unless `out.len()` is exactly `2 * bytes.len()`.
+///
+/// ```
+/// let mut buf = [0u8; 2 * turb1600::DIGEST_BYTES];
+/// let hex = turb1600::encode_hex_to_slice(&turb1600::turb1600_hash(b"abc"), &mut buf);
+/// assert_eq!(hex, turb1600::hash_hex("abc"));
+/// ```
+pub fn encode_hex_to_slice<'a>(bytes: &[u8], out: &'a mut [u8]) -> &'a str {
+    assert_eq!(out.len(), 2 * bytes.len(), "hex buffer has the wrong length");
+    for (pair, &b) in out.chunks_exact_mut(2).zip(bytes) {
+        pair.copy_from_slice(&PAIRS[b as usize]);
+    }
+    // SAFETY: every byte written comes from DIGITS, which is ASCII.
+    unsafe { std::str::from_utf8_unchecked(out) }
+}
+
+/// Append the hex form of `bytes` to `out`.
+pub fn encode_hex_into(bytes: &[u8], out: &mut String) {
+    out.reserve(2 * bytes.len());
+    // SAFETY: only ASCII digit pairs from PAIRS are appended, so
+    // the string stays valid UTF-8.
+    let vec = unsafe { out.as_mut_vec() };
+    for &b in bytes {
+        vec.extend_from_slice(&PAIRS[b as usize]);
+    }
+}
+
+/// Lowercase hex form of `bytes`.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 * bytes.len());
+    encode_hex_into(bytes, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_hex_crate() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(encode_hex(&bytes), hex::encode(&bytes));
+
+        let mut s = String::from("sha:");
+        encode_hex_into(&[0x0f, 0xa0], &mut s);
+        assert_eq!(s, "sha:0fa0");
+
+        let mut buf = [0u8; 6];
+        assert_eq!(encode_hex_to_slice(&[1, 0xfe, 0x7f], &mut buf), "01fe7f");
+    }
+}
@@ -0,0 +1,102 @@
+// =========================================================
+// turb1600 — Passphrase-based key derivation
+//
+// The sponge starts from a KDF-specific IV and absorbs
+//
+//   LE64(|pass|) || pass || LE64(|salt|) || salt
+//   || LE32(iterations) || LE64(out_len)
+//
+// zero-padded to a block. Each iteration then absorbs one
+// block holding LE32(i), costing a full set of main rounds.
+// The key is squeezed from the finalized sponge.
+// =========================================================
+
+use crate::core::{Sponge, KDF_TAG};
+use crate::error::Turb1600Error;
+use crate::key::{zeroize, SecretKey};
+#[cfg(feature = "getrandom")]
+use crate::salt::{generate_salt, SALT_BYTES};
+
+/// Iteration count used by [`derive_key_default`] and `TurbRng::from_passphrase`.
+pub const KDF_DEFAULT_ITERATIONS: u32 = 16_384;
+
+/// Stretch `passphrase` with `salt` into a `len`-byte key.
+///
+/// Panics if `iterations` is zero; see [`try_derive_key`].
+pub fn derive_key(passphrase: &[u8], salt: &[u8], iterations: u32, len: usize) -> SecretKey {
+    try_derive_key(passphrase, salt, iterations, len).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// [`derive_key`], failing with [`Turb1600Error::ZeroIterations`]
+/// instead of panicking.
+pub fn try_derive_key(
+    passphrase: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    len: usize,
+) -> Result<SecretKey, Turb1600Error> {
+    if iterations == 0 {
+        return Err(Turb1600Error::ZeroIterations);
+    }
+
+    let mut sponge = Sponge::with_tag(KDF_TAG);
+    sponge.update(&(passphrase.len() as u64).to_le_bytes());
+    sponge.update(passphrase);
+    sponge.update(&(salt.len() as u64).to_le_bytes());
+    sponge.update(salt);
+    sponge.update(&iterations.to_le_bytes());
+    sponge.update(&(len as u64).to_le_bytes());
+    sponge.pad_block();
+
+    for i in 0..iterations {
+        sponge.update(&i.to_le_bytes());
+        sponge.pad_block();
+    }
+
+    let mut out = vec![0u8; len];
+    sponge.finalize_xof().squeeze(&mut out);
+    let key = SecretKey::new(&out);
+    zeroize(&mut out);
+    Ok(key)
+}
+
+/// [`derive_key`] with [`KDF_DEFAULT_ITERATIONS`].
+pub fn derive_key_default(passphrase: &[u8], salt: &[u8], len: usize) -> SecretKey {
+    derive_key(passphrase, salt, KDF_DEFAULT_ITERATIONS, len)
+}
+
+/// [`derive_key`] with `salt`, or with a fresh [`SALT_BYTES`]-byte
+/// salt from the OS RNG when it is `None`. Returns the key and the
+/// salt used, which must be stored to derive the key again.
+#[cfg(feature = "getrandom")]
+pub fn derive_key_salted(
+    passphrase: &[u8],
+    salt: Option<&[u8]>,
+    iterations: u32,
+    len: usize,
+) -> (SecretKey, Vec<u8>) {
+    let salt = match salt {
+        Some(salt) => salt.to_vec(),
+        None => generate_salt::<SALT_BYTES>().to_vec(),
+    };
+    (derive_key(passphrase, &salt, iterations, len), salt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_key_inputs() {
+        let k = derive_key(b"pass", b"salt", 4, 32);
+        assert_eq!(k.len(), 32);
+        assert_eq!(k, derive_key(b"pass", b"salt", 4, 32));
+        assert_ne!(k, derive_key(b"pass", b"salt", 5, 32));
+        assert_ne!(k, derive_key(b"pass", b"pepper", 4, 32));
+        assert_ne!(k, derive_key(b"passsalt", b"", 4, 32));
+        assert_ne!(
+            k.expose_secret(),
+            &derive_key(b"pass", b"salt", 4, 64).expose_secret()[..32]
+        );
+    }
+}
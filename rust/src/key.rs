@@ -0,0 +1,159 @@
+// =========================================================
+// turb1600 — Secret key material
+// =========================================================
+
+use std::fmt;
+use std::hint::black_box;
+use std::ptr;
+use std::sync::atomic::{compiler_fence, Ordering};
+
+/// Overwrite `bytes` with zeros in a way the optimizer cannot elide.
+#[inline(never)]
+pub(crate) fn zeroize(bytes: &mut [u8]) {
+    for b in bytes.iter_mut() {
+        // SAFETY: `b` is a valid, aligned, exclusive reference.
+        unsafe { ptr::write_volatile(b, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// [`zeroize`] for state lanes.
+#[inline(never)]
+pub(crate) fn zeroize_lanes(lanes: &mut [u64]) {
+    for lane in lanes.iter_mut() {
+        // SAFETY: `lane` is a valid, aligned, exclusive reference.
+        unsafe { ptr::write_volatile(lane, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Compare two byte strings without early exit on the first mismatch.
+///
+/// Only the lengths are compared in variable time.
+#[inline(never)]
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b) {
+        diff |= black_box(x ^ y);
+    }
+    black_box(diff) == 0
+}
+
+/// Key material for the keyed APIs.
+///
+/// The bytes are zeroized on drop, never printed by `Debug` or
+/// `Display`, and compared in constant time.
+pub struct SecretKey {
+    bytes: Box<[u8]>,
+}
+
+impl SecretKey {
+    /// Copy `bytes` into a new key.
+    pub fn new(bytes: &[u8]) -> Self {
+        Self {
+            bytes: bytes.into(),
+        }
+    }
+
+    /// Key length in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Borrow the raw key bytes.
+    ///
+    /// Callers are responsible for not copying them somewhere that
+    /// outlives the key.
+    pub fn expose_secret(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// [`zeroize`] a vector's whole allocation, including spare
+/// capacity that may still hold bytes from before a truncate.
+fn zeroize_vec(bytes: &mut Vec<u8>) {
+    // Within capacity, so this never reallocates
+    bytes.resize(bytes.capacity(), 0);
+    zeroize(bytes);
+}
+
+impl From<Vec<u8>> for SecretKey {
+    /// Take ownership of `bytes`, wiping the vector's whole
+    /// allocation.
+    fn from(mut bytes: Vec<u8>) -> Self {
+        let key = Self::new(&bytes);
+        zeroize_vec(&mut bytes);
+        key
+    }
+}
+
+impl Clone for SecretKey {
+    fn clone(&self) -> Self {
+        Self::new(&self.bytes)
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        zeroize(&mut self.bytes);
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.bytes, &other.bytes)
+    }
+}
+
+impl Eq for SecretKey {}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey([REDACTED])")
+    }
+}
+
+impl fmt::Display for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_key_redacted() {
+        let key = SecretKey::new(b"hunter2");
+        assert!(!format!("{:?}", key).contains("hunter2"));
+        assert!(!format!("{}", key).contains("hunter2"));
+    }
+
+    #[test]
+    fn test_secret_key_eq() {
+        let a = SecretKey::new(b"key-a");
+        assert_eq!(a, SecretKey::from(b"key-a".to_vec()));
+        assert_ne!(a, SecretKey::new(b"key-b"));
+        assert_ne!(a, SecretKey::new(b"key-aa"));
+    }
+
+    #[test]
+    fn test_zeroize_vec_wipes_spare_capacity() {
+        let mut bytes = b"secret key, then truncated".to_vec();
+        bytes.truncate(6);
+        let (ptr, capacity) = (bytes.as_ptr(), bytes.capacity());
+        zeroize_vec(&mut bytes);
+        assert_eq!(bytes.as_ptr(), ptr);
+        assert_eq!(bytes.len(), capacity);
+        assert!(bytes.iter().all(|&b| b == 0));
+    }
+}
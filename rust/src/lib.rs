@@ -1,11 +1,75 @@
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
+// Lets `#[derive(TurbHash)]` output refer to `::turb1600` inside this crate.
+extern crate self as turb1600;
+
+pub mod backend;
+pub mod bench;
+pub mod builder;
+pub mod canonical;
+pub mod cdc;
+pub mod const_hash;
 pub mod core;
+pub mod dir;
+pub mod error;
+pub mod file;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod hexfmt;
+pub mod kdf;
+pub mod key;
+pub mod mac;
+pub mod multiformats;
+pub mod output;
+pub mod pool;
+pub mod presets;
+pub mod random_state;
+pub mod rng;
+#[cfg(feature = "getrandom")]
+pub mod salt;
+#[cfg(feature = "digest")]
+pub mod rustcrypto;
+pub mod segment;
+#[cfg(test)]
+mod testdir;
+pub mod tree;
+pub mod verify;
+pub mod words;
 
-pub use core::turb1600_hash;
+pub use backend::backend_name;
+pub use builder::{BuildError, Turb1600Builder};
+pub use canonical::TurbHash;
+pub use cdc::{Chunk, Chunker};
+pub use const_hash::turb1600_hash_const;
+pub use self::core::{turb1600_hash, turb1600_hash_parts, xof_to_writer, Turb1600, Turb1600Xof, DIGEST_BYTES};
+pub use dir::{hash_dir, hash_dir_with_modes};
+pub use error::Turb1600Error;
+pub use file::{hash_file, hash_file_buffered, hash_file_with_progress, hash_reader};
+pub use hexfmt::{encode_hex, encode_hex_into, encode_hex_to_slice};
+pub use kdf::{derive_key, derive_key_default, try_derive_key, KDF_DEFAULT_ITERATIONS};
+pub use key::SecretKey;
+pub use mac::{turb1600_mac, turb1600_mac_verify};
+pub use output::{Digest, Turb1600Fixed};
+pub use pool::{HasherPool, PooledHasher};
+pub use presets::{turb1600_256, turb1600_256_hash, turb1600_512, turb1600_512_hash};
+pub use random_state::{TurbHasher, TurbRandomState};
+pub use rng::TurbRng;
+pub use segment::{SegmentDigests, SegmentHasher};
+pub use tree::{try_turb1600_tree_hash, turb1600_tree_hash, turb1600_tree_hash_reader, TREE_CHUNK_DEFAULT};
+pub use verify::VerifyingReader;
+pub use words::{encode_words, fingerprint_words};
+#[cfg(feature = "derive")]
+pub use turb1600_derive::TurbHash;
+#[cfg(feature = "getrandom")]
+pub use kdf::derive_key_salted;
+#[cfg(feature = "getrandom")]
+pub use salt::{generate_salt, SALT_BYTES};
+#[cfg(feature = "threads")]
+pub use tree::{turb1600_tree_hash_parallel, turb1600_tree_hash_reader_parallel};
 
 /// Convenience: hash a string to hex
 pub fn hash_hex(data: &str) -> String {
-    let digest = turb1600_hash(data.as_bytes());
-    digest.iter().map(|b| format!("{:02x}", b)).collect()
+    encode_hex(&turb1600_hash(data.as_bytes()))
 }
 
 #[cfg(test)]
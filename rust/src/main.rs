@@ -1,21 +1,114 @@
-use std::{env, fs, process};
+use std::{env, process};
 use std::io::Write;
-use turb1600::turb1600_hash;
+use std::time::{Duration, Instant};
+use base64::Engine;
+use turb1600::multiformats::{multibase_encode, multihash, Multibase, MULTIHASH_CODE_DEFAULT};
+use turb1600::words::{fingerprint_words, WORDS_DEFAULT_BYTES};
+
+mod cli;
+#[cfg(test)]
+mod testdir;
+
+use cli::algorithm::{Algorithm, TREE_CHUNK_MAX};
 
 
 /// Print bytes in hex
 fn print_hex(bytes: &[u8]) {
-    for b in bytes {
-        print!("{:02x}", b);
+    println!("{}", turb1600::encode_hex(bytes));
+}
+
+/// Output encoding for the digest
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Hex,
+    Raw,
+    Sri,
+    Multihash,
+    Randomart,
+    Words,
+}
+
+/// Algorithm prefix in Subresource-Integrity strings
+const SRI_PREFIX: &str = "turb1600-";
+
+/// Format a digest as an SRI integrity string
+fn sri_string(digest: &[u8]) -> String {
+    format!("{}{}", SRI_PREFIX, base64::engine::general_purpose::STANDARD.encode(digest))
+}
+
+/// Check a digest against an SRI metadata string.
+///
+/// The string may list several space-separated hashes; entries for other
+/// algorithms are ignored and `?options` suffixes are stripped.
+fn sri_matches(integrity: &str, digest: &[u8]) -> bool {
+    let expected = sri_string(digest);
+    integrity
+        .split_ascii_whitespace()
+        .map(|entry| entry.split('?').next().unwrap_or(entry))
+        .any(|entry| entry == expected)
+}
+
+/// Hash `bytes` bytes of a file starting at `offset` (to EOF if `None`)
+/// through `buf_size`-byte reads; also returns the number of bytes hashed
+fn hash_file_range(
+    path: &str,
+    offset: u64,
+    bytes: Option<u64>,
+    buf_size: usize,
+) -> std::io::Result<(Vec<u8>, u64)> {
+    use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    // Seeking to the end also sizes block devices, where metadata reports 0
+    let size = file.seek(SeekFrom::End(0))?;
+    let end = match bytes {
+        Some(n) => offset.checked_add(n),
+        None => Some(size),
+    };
+    match end {
+        Some(end) if offset <= size && end <= size => {}
+        _ => return Err(Error::new(ErrorKind::UnexpectedEof, "range extends past end of file")),
     }
-    println!();
+
+    file.seek(SeekFrom::Start(offset))?;
+    let len = end.unwrap_or(size) - offset;
+    turb1600::hash_reader(file.take(len), buf_size).map(|out| (out, len))
+}
+
+/// Download and hash `url`; also returns the body length
+#[cfg(feature = "net")]
+fn hash_url(url: &str) -> (Vec<u8>, u64) {
+    // ureq errors already name the URL
+    cli::fetch::hash_url(url).unwrap_or_else(|e| {
+        eprintln!("turb1600: {}", e);
+        process::exit(1);
+    })
+}
+
+#[cfg(not(feature = "net"))]
+fn hash_url(_url: &str) -> (Vec<u8>, u64) {
+    eprintln!("turb1600: built without network support (enable the `net` feature)");
+    process::exit(1);
 }
 
-/// Print bytes as UTF-8 if possible, else fallback to hex
-fn print_utf8_or_hex(bytes: &[u8]) {
-    match std::str::from_utf8(bytes) {
-        Ok(s) => println!("{}", s),
-        Err(_) => print_hex(bytes),
+/// Load a `--key-file`, exiting on error
+fn load_key(path: &str) -> turb1600::key::SecretKey {
+    cli::manifest::load_key(path).unwrap_or_else(|e| {
+        eprintln!("turb1600: {}", e);
+        process::exit(1);
+    })
+}
+
+/// `--time` report on stderr: elapsed time, bytes hashed and MB/s.
+/// `bytes` is `None` for inputs without a single byte count (`--dir`).
+fn print_time(elapsed: Duration, bytes: Option<u64>) {
+    let secs = elapsed.as_secs_f64();
+    match bytes {
+        Some(n) if secs > 0.0 => {
+            eprintln!("time: {:.6} s, {} bytes, {:.2} MB/s", secs, n, n as f64 / secs / 1e6)
+        }
+        Some(n) => eprintln!("time: {:.6} s, {} bytes", secs, n),
+        None => eprintln!("time: {:.6} s", secs),
     }
 }
 
@@ -26,13 +119,158 @@ fn usage() -> ! {
   turb1600 <string>                 Hash a string
   turb1600 --hex <hex-string>       Hash raw bytes from hex
   turb1600 --file <path>            Hash file contents
+  turb1600 --dir <path>             Hash a directory tree (paths and contents)
   turb1600 --tag <tag> <string>     Hash string with domain tag
+  turb1600 --url <url>              Download and hash a remote resource
+  turb1600 --archive <file>         Hash each file in a tar, tar.gz or zip archive
+  turb1600 --xattr store <path>...  Store file digests in the user.turb1600 xattr
+  turb1600 --xattr verify <path>... Re-verify files against their stored xattr
+  turb1600 --tee [--digest-file <file>]
+                                    Copy stdin to stdout, then print its digest to
+                                    stderr (or write it to <file>)
+  turb1600 serve [--listen <addr>]  Serve POST /hash[?length=N] over HTTP
+  turb1600 --pieces <size> <path>   Digest per <size> piece (e.g. 4MiB) plus a root
+  turb1600 manifest [--algorithm <name>] [--key-file <file>] [--stat] [-b|-t] <path>...
+                                    Print `[<algorithm>:]<hex>  <path>` for every file
+                                    (`<hex> *<path>` with -b/--binary, as sha*sum);
+                                    --stat also records size and mtime; with a key,
+                                    end with a MAC over the entries
+  turb1600 --check <manifest> [--key-file <file>] [--fast|--paranoid]
+                                    Verify the files a manifest lists; with a key, the
+                                    manifest's MAC must verify first; --fast skips
+                                    files whose recorded size and mtime match
+  turb1600 db init|update|verify [--fast|--paranoid] [--db <file>] <dir>
+                                    Maintain a digest/size/mtime database of <dir>;
+                                    --fast skips files whose size and mtime match
+  turb1600 dupes [--delete|--hardlink] [--yes] <dir>...
+                                    List duplicate files grouped by digest; optionally
+                                    delete or hard-link all but the first of each group
+  turb1600 watch [--manifest <file>] <path>...
+                                    Re-hash files as they change, optionally
+                                    reporting against a manifest
 Options:
-  --raw                              Output raw bytes instead of hex"
+  --algorithm <name>                 turb1600 (default), turb1600-256,
+                                     turb1600-512, turb1600-tree or
+                                     turb1600-tree-<chunk>; other algorithms
+                                     print as <name>:<hex>
+  --tree [--chunk <size>]            Tree mode over all cores (default chunk 1MiB,
+                                     at most 1GiB); same as
+                                     --algorithm turb1600-tree[-<size>]
+  --raw                              Output raw bytes instead of hex
+  --randomart                        Show an OpenSSH-style visual fingerprint
+  --format <hex|raw|sri|multihash|words>
+                                     Output encoding (sri: turb1600-<base64>,
+                                     words: first 8 bytes as BIP-39 words)
+  --multibase <base>                 Multibase for --format multihash
+                                     (base16|base32|base58btc|base64|base64url)
+  --multihash-code <code>            Multihash code (default 0x300000, private use)
+  --check-sri <integrity>            Verify the input against an SRI string
+  --verify <hex>                     Verify the input against a hex digest
+  --offset <size>                    With --file: start hashing at this byte offset
+  --bytes <size>                     With --file: hash only this many bytes
+  --buffer-size <size>               With --file: read buffer size (default 1MiB)
+  --time                             Print elapsed time, bytes and MB/s to stderr"
     );
     process::exit(1);
 }
 
+/// Extended attribute holding the hex digest of a file
+#[cfg(all(unix, feature = "xattr"))]
+const XATTR_NAME: &str = "user.turb1600";
+
+/// `--xattr store|verify <path>...`; returns the process exit code
+#[cfg(all(unix, feature = "xattr"))]
+fn run_xattr(mode: &str, paths: &[String]) -> i32 {
+    let store = match mode {
+        "store" => true,
+        "verify" => false,
+        _ => usage(),
+    };
+    if paths.is_empty() {
+        usage();
+    }
+
+    let mut failures = 0;
+    for path in paths {
+        let digest = match turb1600::hash_file(path) {
+            Ok(out) => hex::encode(out),
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                failures += 1;
+                continue;
+            }
+        };
+
+        if store {
+            if let Err(e) = xattr::set(path, XATTR_NAME, digest.as_bytes()) {
+                eprintln!("{}: cannot set {}: {}", path, XATTR_NAME, e);
+                failures += 1;
+            }
+            continue;
+        }
+
+        match xattr::get(path, XATTR_NAME) {
+            Ok(Some(stored)) if stored == digest.as_bytes() => println!("{}: OK", path),
+            Ok(Some(_)) => {
+                println!("{}: FAILED", path);
+                failures += 1;
+            }
+            Ok(None) => {
+                println!("{}: MISSING", path);
+                failures += 1;
+            }
+            Err(e) => {
+                eprintln!("{}: cannot read {}: {}", path, XATTR_NAME, e);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 { 1 } else { 0 }
+}
+
+#[cfg(not(all(unix, feature = "xattr")))]
+fn run_xattr(_mode: &str, _paths: &[String]) -> i32 {
+    eprintln!("turb1600: built without xattr support (enable the `xattr` feature)");
+    1
+}
+
+#[cfg(feature = "archive")]
+fn run_archive(path: &str) -> Result<(), String> {
+    cli::archive::run(path)
+}
+
+#[cfg(not(feature = "archive"))]
+fn run_archive(_path: &str) -> Result<(), String> {
+    Err("built without archive support (enable the `archive` feature)".to_string())
+}
+
+#[cfg(feature = "watch")]
+fn run_watch(manifest: Option<&str>, paths: &[String]) -> Result<(), String> {
+    cli::watch::run(manifest, paths)
+}
+
+#[cfg(not(feature = "watch"))]
+fn run_watch(_manifest: Option<&str>, _paths: &[String]) -> Result<(), String> {
+    Err("built without watch support (enable the `watch` feature)".to_string())
+}
+
+#[cfg(feature = "db")]
+fn run_db(mode: &str, dir: &str, db_path: Option<&str>, fast: bool) -> Result<i32, String> {
+    let mode = match mode {
+        "init" => cli::db::Mode::Init,
+        "update" => cli::db::Mode::Update,
+        "verify" => cli::db::Mode::Verify,
+        _ => usage(),
+    };
+    cli::db::run(mode, dir, db_path, fast)
+}
+
+#[cfg(not(feature = "db"))]
+fn run_db(_mode: &str, _dir: &str, _db_path: Option<&str>, _fast: bool) -> Result<i32, String> {
+    Err("built without database support (enable the `db` feature)".to_string())
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -40,31 +278,373 @@ fn main() {
         usage();
     }
 
-    let mut raw_output = false;
-    let mut arg_start = 1;
+    if args[1] == "serve" {
+        let addr = match args.get(2).map(String::as_str) {
+            None => cli::serve::DEFAULT_LISTEN,
+            Some("--listen") if args.len() == 4 => &args[3],
+            _ => usage(),
+        };
+        if let Err(e) = cli::serve::run(addr) {
+            eprintln!("turb1600: serve: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args[1] == "watch" {
+        let (manifest, paths) = match args.get(2).map(String::as_str) {
+            Some("--manifest") if args.len() > 4 => (Some(args[3].as_str()), &args[4..]),
+            Some("--manifest") | None => usage(),
+            Some(_) => (None, &args[2..]),
+        };
+        if let Err(e) = run_watch(manifest, paths) {
+            eprintln!("turb1600: watch: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args[1] == "db" {
+        if args.len() < 4 {
+            usage();
+        }
+        let (mut db_path, mut fast, mut paranoid) = (None, false, false);
+        let mut i = 3;
+        while i < args.len() - 1 {
+            match args[i].as_str() {
+                "--fast" => fast = true,
+                "--paranoid" => paranoid = true,
+                "--db" if i + 2 < args.len() => {
+                    db_path = Some(args[i + 1].as_str());
+                    i += 1;
+                }
+                _ => usage(),
+            }
+            i += 1;
+        }
+        if fast && paranoid {
+            usage();
+        }
+        match run_db(&args[2], &args[args.len() - 1], db_path, fast) {
+            Ok(code) => process::exit(code),
+            Err(e) => {
+                eprintln!("turb1600: db: {}", e);
+                process::exit(1);
+            }
+        }
+    }
 
-    // Check for --raw
-    if args[1] == "--raw" {
-        raw_output = true;
-        arg_start += 1;
-        if args.len() <= arg_start {
+    if args[1] == "dupes" {
+        let (mut action, mut assume_yes) = (cli::dupes::Action::Report, false);
+        let mut i = 2;
+        while let Some(arg) = args.get(i).filter(|a| a.starts_with("--")) {
+            match arg.as_str() {
+                "--delete" if action == cli::dupes::Action::Report => action = cli::dupes::Action::Delete,
+                "--hardlink" if action == cli::dupes::Action::Report => action = cli::dupes::Action::Hardlink,
+                "--yes" => assume_yes = true,
+                _ => usage(),
+            }
+            i += 1;
+        }
+        if i == args.len() {
             usage();
         }
+        match cli::dupes::run(&args[i..], action, assume_yes) {
+            Ok(code) => process::exit(code),
+            Err(e) => {
+                eprintln!("turb1600: dupes: {}", e);
+                process::exit(1);
+            }
+        }
     }
 
-    let input: Vec<u8> = match args[arg_start].as_str() {
+    if args[1] == "--archive" {
+        if args.len() != 3 {
+            usage();
+        }
+        if let Err(e) = run_archive(&args[2]) {
+            eprintln!("turb1600: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args[1] == "--tee" {
+        let digest_file = match args.len() {
+            2 => None,
+            4 if args[2] == "--digest-file" => Some(args[3].as_str()),
+            _ => usage(),
+        };
+        if let Err(e) = cli::tee::run(digest_file) {
+            eprintln!("turb1600: tee: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args[1] == "--pieces" {
+        if args.len() != 4 {
+            usage();
+        }
+        let size = match cli::parse_size(&args[2]) {
+            Some(n) if n > 0 => n,
+            _ => usage(),
+        };
+        if let Err(e) = cli::pieces::run(size, &args[3]) {
+            eprintln!("{}: {}", args[3], e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args[1] == "manifest" {
+        let mut options = cli::manifest::Options {
+            algorithm: Algorithm::Turb1600,
+            binary: false,
+            stat: false,
+        };
+        let mut key = None;
+        let mut i = 2;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--algorithm" if i + 1 < args.len() => {
+                    options.algorithm = Algorithm::from_name(&args[i + 1]).unwrap_or_else(|| usage());
+                    i += 1;
+                }
+                "--key-file" if i + 1 < args.len() => {
+                    key = Some(load_key(&args[i + 1]));
+                    i += 1;
+                }
+                "--stat" => options.stat = true,
+                "-b" | "--binary" => options.binary = true,
+                "-t" | "--text" => options.binary = false,
+                _ => break,
+            }
+            i += 1;
+        }
+        if i == args.len() {
+            usage();
+        }
+        if let Err(e) = cli::manifest::create(options, key.as_ref(), &args[i..]) {
+            eprintln!("turb1600: manifest: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args[1] == "--check" {
+        if args.len() < 3 {
+            usage();
+        }
+        let (mut key, mut fast, mut paranoid) = (None, false, false);
+        let mut i = 3;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--key-file" if i + 1 < args.len() => {
+                    key = Some(load_key(&args[i + 1]));
+                    i += 1;
+                }
+                "--fast" => fast = true,
+                "--paranoid" => paranoid = true,
+                _ => usage(),
+            }
+            i += 1;
+        }
+        if fast && paranoid {
+            usage();
+        }
+        match cli::manifest::check(&args[2], key.as_ref(), fast) {
+            Ok(code) => process::exit(code),
+            Err(e) => {
+                eprintln!("turb1600: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    if args[1] == "--xattr" {
+        if args.len() < 3 {
+            usage();
+        }
+        process::exit(run_xattr(&args[2], &args[3..]));
+    }
+
+    let mut algorithm = Algorithm::Turb1600;
+    let mut format = Format::Hex;
+    let mut check_sri: Option<String> = None;
+    let mut verify: Option<String> = None;
+    let mut multibase = Multibase::Base58Btc;
+    let mut multihash_code = MULTIHASH_CODE_DEFAULT;
+    let mut offset: Option<u64> = None;
+    let mut bytes: Option<u64> = None;
+    let mut buffer_size = turb1600::file::FILE_BUFFER_DEFAULT;
+    let mut time = false;
+    let mut tree = false;
+    let mut chunk: Option<usize> = None;
+    let mut arg_start = 1;
+
+    // Leading options
+    loop {
+        match args.get(arg_start).map(String::as_str) {
+            Some("--algorithm") => {
+                algorithm = args
+                    .get(arg_start + 1)
+                    .and_then(|name| Algorithm::from_name(name))
+                    .unwrap_or_else(|| usage());
+                arg_start += 2;
+            }
+            Some("--tree") => {
+                tree = true;
+                arg_start += 1;
+            }
+            Some("--chunk") => {
+                chunk = match args.get(arg_start + 1).and_then(|s| cli::parse_size(s)) {
+                    Some(n) if n > 0 && n <= TREE_CHUNK_MAX as u64 => Some(n as usize),
+                    _ => usage(),
+                };
+                arg_start += 2;
+            }
+            Some("--raw") => {
+                format = Format::Raw;
+                arg_start += 1;
+            }
+            Some("--randomart") => {
+                format = Format::Randomart;
+                arg_start += 1;
+            }
+            Some("--format") => {
+                format = match args.get(arg_start + 1).map(String::as_str) {
+                    Some("hex") => Format::Hex,
+                    Some("raw") => Format::Raw,
+                    Some("sri") => Format::Sri,
+                    Some("multihash") => Format::Multihash,
+                    Some("words") => Format::Words,
+                    _ => usage(),
+                };
+                arg_start += 2;
+            }
+            Some("--multibase") => {
+                multibase = args
+                    .get(arg_start + 1)
+                    .and_then(|name| Multibase::from_name(name))
+                    .unwrap_or_else(|| usage());
+                arg_start += 2;
+            }
+            Some("--multihash-code") => {
+                multihash_code = args
+                    .get(arg_start + 1)
+                    .and_then(|code| match code.strip_prefix("0x") {
+                        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                        None => code.parse().ok(),
+                    })
+                    .unwrap_or_else(|| usage());
+                arg_start += 2;
+            }
+            Some("--check-sri") => {
+                check_sri = Some(args.get(arg_start + 1).cloned().unwrap_or_else(|| usage()));
+                arg_start += 2;
+            }
+            Some("--verify") => {
+                verify = Some(args.get(arg_start + 1).cloned().unwrap_or_else(|| usage()));
+                arg_start += 2;
+            }
+            Some("--offset") => {
+                offset = Some(args.get(arg_start + 1).and_then(|s| cli::parse_size(s)).unwrap_or_else(|| usage()));
+                arg_start += 2;
+            }
+            Some("--bytes") => {
+                bytes = Some(args.get(arg_start + 1).and_then(|s| cli::parse_size(s)).unwrap_or_else(|| usage()));
+                arg_start += 2;
+            }
+            Some("--buffer-size") => {
+                buffer_size = match args.get(arg_start + 1).and_then(|s| cli::parse_size(s)) {
+                    Some(n) if n > 0 => usize::try_from(n).unwrap_or_else(|_| usage()),
+                    _ => usage(),
+                };
+                arg_start += 2;
+            }
+            Some("--time") => {
+                time = true;
+                arg_start += 1;
+            }
+            _ => break,
+        }
+    }
+
+    match (tree, chunk, algorithm) {
+        (true, _, _) => algorithm = Algorithm::Tree(chunk.unwrap_or(turb1600::TREE_CHUNK_DEFAULT)),
+        (false, Some(chunk), Algorithm::Tree(_)) => algorithm = Algorithm::Tree(chunk),
+        (false, Some(_), _) => usage(),
+        (false, None, _) => {}
+    }
+
+    let ranged = offset.is_some() || bytes.is_some();
+    if ranged && args.get(arg_start).map(String::as_str) != Some("--file") {
+        usage();
+    }
+
+    if args.len() <= arg_start {
+        usage();
+    }
+
+    if algorithm != Algorithm::Turb1600 {
+        // SRI and multihash labels name plain turb1600; ranges, trees and URLs are streamed
+        let sri = check_sri.is_some() || matches!(format, Format::Sri | Format::Multihash);
+        let streamed = ranged || matches!(args[arg_start].as_str(), "--dir" | "--url");
+        if sri || streamed {
+            eprintln!("turb1600: --algorithm {} only applies to --file, --hex, --tag and strings, with hex, raw, randomart or words output", algorithm.name());
+            process::exit(1);
+        }
+    }
+
+    let started = Instant::now();
+    // Digest, and the number of bytes hashed where there is one
+    let (out, hashed): (Vec<u8>, Option<u64>) = match args[arg_start].as_str() {
         "--hex" => {
             if args.len() <= arg_start + 1 {
                 usage();
             }
-            hex::decode(&args[arg_start + 1]).expect("Invalid hex input")
+            let data = hex::decode(&args[arg_start + 1]).expect("Invalid hex input");
+            (algorithm.hash(&data), Some(data.len() as u64))
         }
 
         "--file" => {
             if args.len() <= arg_start + 1 {
                 usage();
             }
-            fs::read(&args[arg_start + 1]).expect("Failed to read file")
+            let path = &args[arg_start + 1];
+            let result = if ranged {
+                hash_file_range(path, offset.unwrap_or(0), bytes, buffer_size)
+            } else {
+                algorithm
+                    .hash_file_buffered(path, buffer_size)
+                    .and_then(|out| Ok((out, std::fs::metadata(path)?.len())))
+            };
+            let (out, len) = result.unwrap_or_else(|e| {
+                eprintln!("{}: {}", path, e);
+                process::exit(1);
+            });
+            (out, Some(len))
+        }
+
+        "--dir" => {
+            let path = args.get(arg_start + 1).unwrap_or_else(|| usage());
+            let out = turb1600::hash_dir(path).unwrap_or_else(|e| {
+                eprintln!("{}: {}", path, e);
+                process::exit(1);
+            });
+            (out, None)
+        }
+
+        "--url" => {
+            match args.get(arg_start + 2).map(String::as_str) {
+                None => {}
+                // `--url <url> --verify <hex>` reads naturally, so accept it trailing too
+                Some("--verify") if args.len() == arg_start + 4 => verify = Some(args[arg_start + 3].clone()),
+                _ => usage(),
+            }
+            let (out, len) = hash_url(args.get(arg_start + 1).unwrap_or_else(|| usage()));
+            (out, Some(len))
         }
 
         "--tag" => {
@@ -75,18 +655,41 @@ fn main() {
             v.extend_from_slice(args[arg_start + 1].as_bytes());
             v.push(0x00); // domain separator
             v.extend_from_slice(args[arg_start + 2].as_bytes());
-            v
+            (algorithm.hash(&v), Some(v.len() as u64))
         }
 
-        _ => args[arg_start].as_bytes().to_vec(),
+        input => (algorithm.hash(input.as_bytes()), Some(input.len() as u64)),
     };
+    let elapsed = started.elapsed();
+    let report_time = || {
+        if time {
+            print_time(elapsed, hashed);
+        }
+    };
+
+    if let Some(integrity) = check_sri {
+        let ok = sri_matches(&integrity, &out);
+        println!("{}", if ok { "OK" } else { "FAILED" });
+        report_time();
+        process::exit(if ok { 0 } else { 1 });
+    }
 
-    let out = turb1600_hash(&input);
+    if let Some(expected) = verify {
+        let ok = hex::decode(expected.trim()).is_ok_and(|expected| expected == out);
+        println!("{}", if ok { "OK" } else { "FAILED" });
+        report_time();
+        process::exit(if ok { 0 } else { 1 });
+    }
 
-    if raw_output {
+    match format {
         // print raw bytes to stdout
-        std::io::stdout().write_all(&out).expect("Failed to write output");
-    } else {
-        print_hex(&out);
+        Format::Raw => std::io::stdout().write_all(&out).expect("Failed to write output"),
+        Format::Sri => println!("{}", sri_string(&out)),
+        Format::Multihash => println!("{}", multibase_encode(multibase, &multihash(multihash_code, &out))),
+        Format::Randomart => cli::randomart::print(algorithm, &out),
+        Format::Words => println!("{}", fingerprint_words(&out, WORDS_DEFAULT_BYTES)),
+        Format::Hex if algorithm == Algorithm::Turb1600 => print_hex(&out),
+        Format::Hex => println!("{}", algorithm.tag(&out)),
     }
+    report_time();
 }
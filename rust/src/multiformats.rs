@@ -0,0 +1,304 @@
+// =========================================================
+// turb1600 — Multihash / multibase encoding
+//
+//   multihash = uvarint(code) || uvarint(len) || digest
+//   multibase = <prefix char> || encode(bytes)
+//
+// turb1600 has no registered multicodec entry; the default code
+// is the first value of the multicodec private-use range and
+// callers may pass their own.
+// =========================================================
+
+use std::fmt;
+
+use base64::Engine;
+
+use crate::hexfmt::encode_hex;
+
+/// Default multihash code (multicodec private-use range).
+pub const MULTIHASH_CODE_DEFAULT: u64 = 0x30_0000;
+
+/// Malformed multihash or multibase input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MultiformatError {
+    /// A varint is truncated or longer than 9 bytes.
+    Varint,
+    /// The declared digest length does not match the remaining bytes.
+    Length,
+    /// The multibase prefix is unknown or missing.
+    UnknownBase,
+    /// The text is not valid in the selected base.
+    Encoding,
+}
+
+impl fmt::Display for MultiformatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MultiformatError::Varint => "invalid varint",
+            MultiformatError::Length => "digest length mismatch",
+            MultiformatError::UnknownBase => "unknown multibase prefix",
+            MultiformatError::Encoding => "invalid multibase text",
+        })
+    }
+}
+
+impl std::error::Error for MultiformatError {}
+
+// =========================================================
+// Multihash
+// =========================================================
+
+fn write_uvarint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn read_uvarint(bytes: &[u8]) -> Result<(u64, &[u8]), MultiformatError> {
+    let mut v = 0u64;
+    for (i, &b) in bytes.iter().enumerate().take(9) {
+        v |= u64::from(b & 0x7f) << (7 * i);
+        if b & 0x80 == 0 {
+            return Ok((v, &bytes[i + 1..]));
+        }
+    }
+    Err(MultiformatError::Varint)
+}
+
+/// Wrap `digest` as a multihash under `code`.
+pub fn multihash(code: u64, digest: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(digest.len() + 12);
+    write_uvarint(&mut out, code);
+    write_uvarint(&mut out, digest.len() as u64);
+    out.extend_from_slice(digest);
+    out
+}
+
+/// Split a multihash into its code and digest.
+pub fn parse_multihash(bytes: &[u8]) -> Result<(u64, &[u8]), MultiformatError> {
+    let (code, rest) = read_uvarint(bytes)?;
+    let (len, digest) = read_uvarint(rest)?;
+    if digest.len() as u64 != len {
+        return Err(MultiformatError::Length);
+    }
+    Ok((code, digest))
+}
+
+// =========================================================
+// Multibase
+// =========================================================
+
+/// Supported multibase encodings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multibase {
+    /// `f`: lowercase hex
+    Base16,
+    /// `b`: RFC 4648 lowercase, no padding
+    Base32,
+    /// `z`: Bitcoin base58
+    Base58Btc,
+    /// `m`: RFC 4648, no padding
+    Base64,
+    /// `u`: RFC 4648 URL-safe, no padding
+    Base64Url,
+}
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+impl Multibase {
+    pub fn prefix(self) -> char {
+        match self {
+            Multibase::Base16 => 'f',
+            Multibase::Base32 => 'b',
+            Multibase::Base58Btc => 'z',
+            Multibase::Base64 => 'm',
+            Multibase::Base64Url => 'u',
+        }
+    }
+
+    pub fn from_prefix(c: char) -> Option<Self> {
+        Some(match c {
+            'f' => Multibase::Base16,
+            'b' => Multibase::Base32,
+            'z' => Multibase::Base58Btc,
+            'm' => Multibase::Base64,
+            'u' => Multibase::Base64Url,
+            _ => return None,
+        })
+    }
+
+    /// Look up a base by its multibase name (e.g. `base58btc`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "base16" => Multibase::Base16,
+            "base32" => Multibase::Base32,
+            "base58btc" => Multibase::Base58Btc,
+            "base64" => Multibase::Base64,
+            "base64url" => Multibase::Base64Url,
+            _ => return None,
+        })
+    }
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() / 5 + usize::from(bytes.len() % 5 != 0)) * 8);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &b in bytes {
+        acc = (acc << 8) | u32::from(b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((acc >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((acc << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for c in text.bytes() {
+        let v = BASE32_ALPHABET.iter().position(|&a| a == c)? as u32;
+        acc = (acc << 5) | v;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+fn base58_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+
+    // Little-endian base-58 digits of the big-endian input
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for &b in &bytes[zeros..] {
+        let mut carry = u32::from(b);
+        for d in digits.iter_mut() {
+            carry += u32::from(*d) << 8;
+            *d = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut out = String::with_capacity(zeros + digits.len());
+    out.push_str(&"1".repeat(zeros));
+    out.extend(digits.iter().rev().map(|&d| BASE58_ALPHABET[d as usize] as char));
+    out
+}
+
+fn base58_decode(text: &str) -> Option<Vec<u8>> {
+    let zeros = text.bytes().take_while(|&c| c == b'1').count();
+
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len());
+    for c in text.bytes().skip(zeros) {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for b in bytes.iter_mut() {
+            carry += u32::from(*b) * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut out = vec![0u8; zeros];
+    out.extend(bytes.iter().rev());
+    Some(out)
+}
+
+/// Encode `bytes` as multibase text.
+pub fn multibase_encode(base: Multibase, bytes: &[u8]) -> String {
+    use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+
+    let body = match base {
+        Multibase::Base16 => encode_hex(bytes),
+        Multibase::Base32 => base32_encode(bytes),
+        Multibase::Base58Btc => base58_encode(bytes),
+        Multibase::Base64 => STANDARD_NO_PAD.encode(bytes),
+        Multibase::Base64Url => URL_SAFE_NO_PAD.encode(bytes),
+    };
+
+    let mut out = String::with_capacity(body.len() + 1);
+    out.push(base.prefix());
+    out.push_str(&body);
+    out
+}
+
+/// Decode multibase text, returning the base it used.
+pub fn multibase_decode(text: &str) -> Result<(Multibase, Vec<u8>), MultiformatError> {
+    use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+
+    let mut chars = text.chars();
+    let base = chars
+        .next()
+        .and_then(Multibase::from_prefix)
+        .ok_or(MultiformatError::UnknownBase)?;
+    let body = chars.as_str();
+
+    let bytes = match base {
+        Multibase::Base16 => hex::decode(body).ok(),
+        Multibase::Base32 => base32_decode(body),
+        Multibase::Base58Btc => base58_decode(body),
+        Multibase::Base64 => STANDARD_NO_PAD.decode(body).ok(),
+        Multibase::Base64Url => URL_SAFE_NO_PAD.decode(body).ok(),
+    };
+
+    bytes.map(|b| (base, b)).ok_or(MultiformatError::Encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multihash_roundtrip() {
+        let digest = crate::turb1600_hash(b"abc");
+        let mh = multihash(MULTIHASH_CODE_DEFAULT, &digest);
+        // 0x300000 -> 80 80 c0 01, 128 -> 80 01
+        assert_eq!(&mh[..6], &[0x80, 0x80, 0xc0, 0x01, 0x80, 0x01]);
+        assert_eq!(parse_multihash(&mh), Ok((MULTIHASH_CODE_DEFAULT, &digest[..])));
+        assert_eq!(parse_multihash(&mh[..mh.len() - 1]), Err(MultiformatError::Length));
+    }
+
+    #[test]
+    fn test_multibase_known_values() {
+        // Vectors from the multibase specification ("yes mani !")
+        let input = b"yes mani !";
+        assert_eq!(multibase_encode(Multibase::Base16, input), "f796573206d616e692021");
+        assert_eq!(multibase_encode(Multibase::Base32, input), "bpfsxgidnmfxgsibb");
+        assert_eq!(multibase_encode(Multibase::Base58Btc, input), "z7paNL19xttacUY");
+        assert_eq!(multibase_encode(Multibase::Base64, input), "meWVzIG1hbmkgIQ");
+        assert_eq!(multibase_encode(Multibase::Base64Url, input), "ueWVzIG1hbmkgIQ");
+    }
+
+    #[test]
+    fn test_multibase_roundtrip() {
+        let data = [0u8, 0, 1, 2, 255, 254, 7];
+        for base in [
+            Multibase::Base16,
+            Multibase::Base32,
+            Multibase::Base58Btc,
+            Multibase::Base64,
+            Multibase::Base64Url,
+        ] {
+            let text = multibase_encode(base, &data);
+            assert_eq!(multibase_decode(&text), Ok((base, data.to_vec())));
+        }
+        assert_eq!(multibase_decode("Qabc"), Err(MultiformatError::UnknownBase));
+    }
+}
@@ -0,0 +1,259 @@
+// =========================================================
+// turb1600 — Fixed-size digest values
+//
+// `Digest` holds a full 128-byte digest and hands out prefixes of
+// a length fixed at compile time. `Turb1600Fixed<OUT>` is a hasher
+// whose output length is part of its type: `finalize` returns
+// `[u8; OUT]`, and OUT is bound into the IV exactly as
+// `Turb1600::builder().output_len(OUT)` does, so a 32-byte digest
+// is not a prefix of the full one and cannot pass for a truncation.
+//
+// Truncation and security level: the 512-bit capacity caps
+// generic security at 256 bits. An N-byte prefix offers about
+// 4N bits of collision resistance and 8N bits of preimage
+// resistance, up to that cap:
+//
+//   N = 16  →  64-bit collision  (identifiers, never integrity)
+//   N = 32  → 128-bit collision  (recommended short form)
+//   N = 64  → 256-bit collision  (full strength)
+//
+// Beyond 64 bytes the extra output adds no security; keep the
+// whole digest only to interoperate with tools that print it.
+// =========================================================
+
+use std::fmt;
+use std::io::{self, Read};
+use std::str::FromStr;
+
+use crate::core::{Turb1600, DIGEST_BYTES};
+use crate::error::Turb1600Error;
+use crate::hexfmt::{encode_hex, encode_hex_to_slice};
+
+/// Shortest prefix [`Digest::truncate`] will produce.
+pub const TRUNCATE_MIN_BYTES: usize = 16;
+
+/// A full turb1600 digest.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Digest([u8; DIGEST_BYTES]);
+
+/// Rejects truncation lengths at compile time.
+struct TruncateLen<const N: usize>;
+
+impl<const N: usize> TruncateLen<N> {
+    const CHECK: () = assert!(
+        N >= TRUNCATE_MIN_BYTES && N <= DIGEST_BYTES,
+        "truncation length must be between 16 and 128 bytes"
+    );
+}
+
+impl Digest {
+    /// Hash `data` in one shot.
+    pub fn of(data: &[u8]) -> Self {
+        let mut hasher = Turb1600::new();
+        hasher.update(data);
+        hasher.finalize_digest()
+    }
+
+    pub fn as_bytes(&self) -> &[u8; DIGEST_BYTES] {
+        &self.0
+    }
+
+    /// `Ok` if `data` hashes to this digest, else
+    /// [`Turb1600Error::DigestMismatch`].
+    pub fn verify(&self, data: &[u8]) -> Result<(), Turb1600Error> {
+        match Digest::of(data) == *self {
+            true => Ok(()),
+            false => Err(Turb1600Error::DigestMismatch),
+        }
+    }
+
+    /// The first `N` bytes of the digest.
+    ///
+    /// `N` is checked at compile time to lie in 16..=128; see the
+    /// module docs for the security each length provides.
+    ///
+    /// ```
+    /// let id: [u8; 32] = turb1600::Digest::of(b"abc").truncate();
+    /// ```
+    ///
+    /// ```compile_fail
+    /// let id: [u8; 129] = turb1600::Digest::of(b"abc").truncate();
+    /// ```
+    pub fn truncate<const N: usize>(&self) -> [u8; N] {
+        let () = TruncateLen::<N>::CHECK;
+
+        let mut out = [0u8; N];
+        out.copy_from_slice(&self.0[..N]);
+        out
+    }
+
+    pub fn to_hex(&self) -> String {
+        encode_hex(&self.0)
+    }
+}
+
+impl Turb1600 {
+    /// Finish hashing into a [`Digest`].
+    ///
+    /// Panics unless [`output_len`](Self::output_len) is [`DIGEST_BYTES`].
+    pub fn finalize_digest(self) -> Digest {
+        let mut out = [0u8; DIGEST_BYTES];
+        self.finalize_into(&mut out);
+        Digest(out)
+    }
+}
+
+/// Hasher with a compile-time output length of `OUT` bytes.
+///
+/// `Turb1600Fixed<128>` is plain turb1600; any other length gives
+/// an unrelated digest, as with the builder's `output_len`.
+///
+/// ```
+/// use turb1600::Turb1600Fixed;
+///
+/// let mut h = Turb1600Fixed::<32>::new();
+/// h.update(b"asset");
+/// let id: [u8; 32] = h.finalize();
+/// ```
+///
+/// ```compile_fail
+/// let h = turb1600::Turb1600Fixed::<0>::new();
+/// ```
+#[derive(Clone)]
+pub struct Turb1600Fixed<const OUT: usize> {
+    inner: Turb1600,
+}
+
+impl<const OUT: usize> Turb1600Fixed<OUT> {
+    const CHECK: () = assert!(OUT > 0, "output length must be non-zero");
+
+    pub fn new() -> Self {
+        let () = Self::CHECK;
+
+        let inner = Turb1600::builder()
+            .output_len(OUT)
+            .build()
+            .expect("non-zero output length is always accepted");
+        Self { inner }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// See [`Turb1600::update_reader`].
+    pub fn update_reader<R: Read>(&mut self, reader: R) -> io::Result<u64> {
+        self.inner.update_reader(reader)
+    }
+
+    pub fn finalize(self) -> [u8; OUT] {
+        let mut out = [0u8; OUT];
+        self.inner.finalize_into(&mut out);
+        out
+    }
+}
+
+impl<const OUT: usize> Default for Turb1600Fixed<OUT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<[u8; DIGEST_BYTES]> for Digest {
+    fn from(bytes: [u8; DIGEST_BYTES]) -> Self {
+        Digest(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for Digest {
+    type Error = Turb1600Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        bytes.try_into().map(Digest).map_err(|_| Turb1600Error::DigestLength {
+            expected: DIGEST_BYTES,
+            actual: bytes.len(),
+        })
+    }
+}
+
+/// Parses the hex form printed by `Display`, in either case.
+impl FromStr for Digest {
+    type Err = Turb1600Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|_| Turb1600Error::InvalidHex)?;
+        Digest::try_from(&bytes[..])
+    }
+}
+
+impl From<Digest> for Vec<u8> {
+    fn from(digest: Digest) -> Self {
+        digest.0.to_vec()
+    }
+}
+
+impl AsRef<[u8]> for Digest {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = [0u8; 2 * DIGEST_BYTES];
+        f.write_str(encode_hex_to_slice(&self.0, &mut buf))
+    }
+}
+
+impl fmt::Debug for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Digest({})", self.to_hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_is_prefix() {
+        let full = crate::turb1600_hash(b"abc");
+        let digest = Digest::of(b"abc");
+        assert_eq!(Vec::from(digest), full);
+        assert_eq!(digest.truncate::<32>(), full[..32]);
+        assert_eq!(digest.truncate::<DIGEST_BYTES>(), full[..]);
+        assert_eq!(Digest::try_from(&full[..]).unwrap(), digest);
+        assert!(Digest::try_from(&full[..64]).is_err());
+    }
+
+    #[test]
+    fn test_parse_and_verify() {
+        let digest = Digest::of(b"abc");
+        assert_eq!(digest.to_string().parse::<Digest>().unwrap(), digest);
+        assert_eq!(digest.to_string().to_uppercase().parse::<Digest>().unwrap(), digest);
+        assert!(matches!("xyz".parse::<Digest>(), Err(Turb1600Error::InvalidHex)));
+        assert!(matches!(
+            "abcd".parse::<Digest>(),
+            Err(Turb1600Error::DigestLength { expected: DIGEST_BYTES, actual: 2 })
+        ));
+
+        assert!(digest.verify(b"abc").is_ok());
+        assert!(matches!(digest.verify(b"abd"), Err(Turb1600Error::DigestMismatch)));
+    }
+
+    #[test]
+    fn test_fixed_binds_output_len() {
+        let mut full = Turb1600Fixed::<DIGEST_BYTES>::new();
+        full.update(b"abc");
+        assert_eq!(full.finalize(), Digest::of(b"abc").truncate::<DIGEST_BYTES>());
+
+        let mut short = Turb1600Fixed::<32>::new();
+        short.update(b"abc");
+        let short = short.finalize();
+        assert_ne!(short, Digest::of(b"abc").truncate::<32>());
+
+        let mut configured = Turb1600::builder().output_len(32).build().unwrap();
+        configured.update(b"abc");
+        assert_eq!(configured.finalize(), short);
+    }
+}
@@ -0,0 +1,190 @@
+// =========================================================
+// turb1600 — Reusable hashers for servers
+//
+// Building a hasher derives its initial state by running the
+// permutation over a domain tag (plus key and parameters for
+// builder hashers). A service hashing many small payloads can
+// skip that work: `HasherPool` keeps idle hashers that were
+// `reset` to the template's initial state and hands them out
+// again.
+//
+// A single-threaded caller can keep one hasher and call
+// `Turb1600::finalize_into_reset` instead; the pool is for
+// sharing across request handlers.
+// =========================================================
+
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+use crate::core::Turb1600;
+
+/// Default number of idle hashers a pool keeps.
+pub const POOL_MAX_IDLE_DEFAULT: usize = 64;
+
+/// Thread-safe pool of preinitialized hashers.
+///
+/// ```
+/// use turb1600::{HasherPool, DIGEST_BYTES};
+///
+/// let pool = HasherPool::new();
+/// let mut out = [0u8; DIGEST_BYTES];
+/// pool.hash_into(b"request body", &mut out);
+///
+/// let mut h = pool.get();
+/// h.update(b"request ");
+/// h.update(b"body");
+/// assert_eq!(h.finalize(), out);
+/// ```
+pub struct HasherPool {
+    template: Turb1600,
+    idle: Mutex<Vec<Turb1600>>,
+    max_idle: usize,
+}
+
+impl HasherPool {
+    /// Pool of plain turb1600 hashers.
+    pub fn new() -> Self {
+        Self::with_template(Turb1600::new())
+    }
+
+    /// Pool of hashers that start where `template` starts, e.g. one
+    /// from [`Turb1600::builder`] with a key or output length. Data
+    /// already absorbed by `template` is discarded.
+    pub fn with_template(mut template: Turb1600) -> Self {
+        template.reset();
+        Self {
+            template,
+            idle: Mutex::new(Vec::new()),
+            max_idle: POOL_MAX_IDLE_DEFAULT,
+        }
+    }
+
+    /// Keep at most `max_idle` returned hashers; extra ones are dropped.
+    pub fn with_max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Digest length of the pooled hashers.
+    pub fn output_len(&self) -> usize {
+        self.template.output_len()
+    }
+
+    /// Take a fresh hasher; it goes back to the pool when dropped
+    /// or finalized.
+    pub fn get(&self) -> PooledHasher<'_> {
+        let hasher = self.lock().pop().unwrap_or_else(|| self.template.clone());
+        PooledHasher {
+            pool: self,
+            hasher: Some(hasher),
+        }
+    }
+
+    /// Hash `data` into `out` without allocating.
+    ///
+    /// Panics unless `out.len()` equals [`output_len`](Self::output_len).
+    pub fn hash_into(&self, data: &[u8], out: &mut [u8]) {
+        let mut h = self.get();
+        h.update(data);
+        h.finalize_into(out);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Turb1600>> {
+        // The idle list is valid whatever a panicking holder was doing
+        self.idle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn put(&self, mut hasher: Turb1600) {
+        hasher.reset();
+        let mut idle = self.lock();
+        if idle.len() < self.max_idle {
+            idle.push(hasher);
+        }
+    }
+}
+
+impl Default for HasherPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hasher borrowed from a [`HasherPool`]; derefs to [`Turb1600`].
+pub struct PooledHasher<'a> {
+    pool: &'a HasherPool,
+    /// `None` only while being returned
+    hasher: Option<Turb1600>,
+}
+
+impl PooledHasher<'_> {
+    /// Write the digest into `out` and return the hasher to the pool.
+    ///
+    /// Panics unless `out.len()` equals the pool's output length.
+    pub fn finalize_into(mut self, out: &mut [u8]) {
+        let hasher = self.hasher.as_mut().expect("present until drop");
+        hasher.finalize_into_reset(out);
+    }
+
+    pub fn finalize(self) -> Vec<u8> {
+        let mut out = vec![0u8; self.output_len()];
+        self.finalize_into(&mut out);
+        out
+    }
+}
+
+impl Deref for PooledHasher<'_> {
+    type Target = Turb1600;
+
+    fn deref(&self) -> &Turb1600 {
+        self.hasher.as_ref().expect("present until drop")
+    }
+}
+
+impl DerefMut for PooledHasher<'_> {
+    fn deref_mut(&mut self) -> &mut Turb1600 {
+        self.hasher.as_mut().expect("present until drop")
+    }
+}
+
+impl Drop for PooledHasher<'_> {
+    fn drop(&mut self) {
+        if let Some(hasher) = self.hasher.take() {
+            self.pool.put(hasher);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::turb1600_hash;
+    use crate::key::SecretKey;
+
+    #[test]
+    fn test_reset_and_reuse_match_fresh_hashers() {
+        let mut h = Turb1600::new();
+        h.update(b"discarded");
+        h.reset();
+        h.update(b"abc");
+        let mut out = [0u8; 128];
+        h.finalize_into_reset(&mut out);
+        assert_eq!(out.to_vec(), turb1600_hash(b"abc"));
+        h.update(b"def");
+        assert_eq!(h.finalize(), turb1600_hash(b"def"));
+
+        let key = SecretKey::new(b"pool key");
+        let template = Turb1600::builder().key(&key).output_len(32).build().unwrap();
+        let pool = HasherPool::with_template(template.clone()).with_max_idle(1);
+        for msg in [&b"one"[..], b"two", b""] {
+            let mut expected = template.clone();
+            expected.update(msg);
+            let mut h = pool.get();
+            h.update(msg);
+            assert_eq!(h.finalize(), expected.finalize());
+        }
+        // An abandoned hasher is reset before reuse
+        pool.get().update(b"abandoned");
+        assert_eq!(pool.lock().len(), 1);
+        assert_eq!(pool.get().finalize(), template.finalize());
+    }
+}
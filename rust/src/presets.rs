@@ -0,0 +1,96 @@
+// =========================================================
+// turb1600 — Reduced-output presets
+//
+//   turb1600-256   32-byte digest
+//   turb1600-512   64-byte digest
+//
+// Each preset starts from its own IV, seeded with a release tag
+// that names its output size, so a turb1600-256 digest is neither
+// a prefix of the full turb1600 digest nor equal to the builder's
+// `output_len(32)`. Callers who want short digests should use
+// these rather than truncating: the algorithm name then says
+// exactly how a stored digest was made.
+//
+// Both keep the full 512-bit capacity; turb1600-256 gives 128-bit
+// collision resistance and turb1600-512 the full 256 bits.
+// =========================================================
+
+use crate::core::{Sponge, Turb1600, TURB256_TAG, TURB512_TAG};
+
+/// Digest size of turb1600-256 in bytes.
+pub const TURB256_BYTES: usize = 32;
+/// Digest size of turb1600-512 in bytes.
+pub const TURB512_BYTES: usize = 64;
+
+/// Incremental turb1600-256 hasher; `finalize` returns 32 bytes.
+///
+/// ```
+/// let mut h = turb1600::turb1600_256();
+/// h.update(b"abc");
+/// assert_eq!(h.finalize(), turb1600::turb1600_256_hash(b"abc"));
+/// ```
+pub fn turb1600_256() -> Turb1600 {
+    Turb1600::from_parts(Sponge::with_tag(TURB256_TAG), TURB256_BYTES)
+}
+
+/// Incremental turb1600-512 hasher; `finalize` returns 64 bytes.
+pub fn turb1600_512() -> Turb1600 {
+    Turb1600::from_parts(Sponge::with_tag(TURB512_TAG), TURB512_BYTES)
+}
+
+/// One-shot turb1600-256.
+pub fn turb1600_256_hash(data: &[u8]) -> [u8; TURB256_BYTES] {
+    let mut out = [0u8; TURB256_BYTES];
+    let mut h = turb1600_256();
+    h.update(data);
+    h.finalize_into(&mut out);
+    out
+}
+
+/// One-shot turb1600-512.
+pub fn turb1600_512_hash(data: &[u8]) -> [u8; TURB512_BYTES] {
+    let mut out = [0u8; TURB512_BYTES];
+    let mut h = turb1600_512();
+    h.update(data);
+    h.finalize_into(&mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::turb1600_hash;
+
+    #[test]
+    fn test_known_answers() {
+        assert_eq!(
+            hex::encode(turb1600_256_hash(b"abc")),
+            "ca35fc740056f2aec55ba236408097245f1f4af2cfb4c38a8e9c56a67f9d8646"
+        );
+        assert_eq!(
+            hex::encode(turb1600_512_hash(b"abc")),
+            "b80400cdc91076df905f33fbe4abf1900eb099b96dad23b7fbdf62a417e7450b\
+             e0986e365f59fd437f065fa987a9e11f272cee286a999212347c635b9a898fcb"
+        );
+    }
+
+    #[test]
+    fn test_presets_are_domain_separated() {
+        let full = turb1600_hash(b"abc");
+        let short = turb1600_256_hash(b"abc");
+        let mid = turb1600_512_hash(b"abc");
+        assert_ne!(short[..], full[..TURB256_BYTES]);
+        assert_ne!(mid[..], full[..TURB512_BYTES]);
+        assert_ne!(short[..], mid[..TURB256_BYTES]);
+
+        let mut configured = Turb1600::builder().output_len(TURB256_BYTES).build().unwrap();
+        configured.update(b"abc");
+        assert_ne!(configured.finalize(), short);
+
+        let mut h = turb1600_512();
+        h.update(b"a");
+        h.update(b"bc");
+        assert_eq!(h.output_len(), TURB512_BYTES);
+        assert_eq!(h.finalize(), mid);
+    }
+}
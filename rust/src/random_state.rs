@@ -0,0 +1,118 @@
+// =========================================================
+// turb1600 — Keyed BuildHasher for HashMap / HashSet
+//
+// `TurbRandomState` keys the MAC construction with per-process
+// random bytes, so bucket positions cannot be predicted by an
+// attacker choosing the keys. The keyed sponge is prepared once
+// per `TurbRandomState`; each hasher starts from a copy of it.
+// =========================================================
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::OnceLock;
+
+use crate::core::Turb1600;
+use crate::key::SecretKey;
+
+const PROCESS_KEY_BYTES: usize = 32;
+
+/// Random key shared by every `TurbRandomState::new()` in the process.
+///
+/// Drawn from std's `RandomState`, which is seeded from the OS RNG.
+fn process_key() -> &'static SecretKey {
+    static KEY: OnceLock<SecretKey> = OnceLock::new();
+    KEY.get_or_init(|| {
+        let mut bytes = Vec::with_capacity(PROCESS_KEY_BYTES);
+        for i in 0..PROCESS_KEY_BYTES / 8 {
+            let mut h = RandomState::new().build_hasher();
+            h.write_usize(i);
+            bytes.extend_from_slice(&h.finish().to_le_bytes());
+        }
+        SecretKey::from(bytes)
+    })
+}
+
+/// `BuildHasher` producing keyed turb1600 hashers.
+#[derive(Clone)]
+pub struct TurbRandomState {
+    keyed: Turb1600,
+}
+
+impl TurbRandomState {
+    /// Use the per-process random key.
+    pub fn new() -> Self {
+        Self::with_key(process_key())
+    }
+
+    /// Use an explicit key, e.g. to reproduce bucket layouts in tests.
+    pub fn with_key(key: &SecretKey) -> Self {
+        Self {
+            keyed: Turb1600::new_keyed(key),
+        }
+    }
+}
+
+impl Default for TurbRandomState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BuildHasher for TurbRandomState {
+    type Hasher = TurbHasher;
+
+    fn build_hasher(&self) -> TurbHasher {
+        TurbHasher {
+            inner: self.keyed.clone(),
+        }
+    }
+}
+
+/// `Hasher` returned by [`TurbRandomState`].
+#[derive(Clone)]
+pub struct TurbHasher {
+    inner: Turb1600,
+}
+
+impl Hasher for TurbHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.inner.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        let mut out = [0u8; 8];
+        self.inner.clone().finalize_xof().squeeze(&mut out);
+        u64::from_le_bytes(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    #[test]
+    fn test_random_state_keyed() {
+        let a = SecretKey::new(b"a");
+        let b = SecretKey::new(b"b");
+        let hash = |s: &TurbRandomState| {
+            let mut h = s.build_hasher();
+            "key".hash(&mut h);
+            h.finish()
+        };
+
+        assert_eq!(hash(&TurbRandomState::with_key(&a)), hash(&TurbRandomState::with_key(&a)));
+        assert_ne!(hash(&TurbRandomState::with_key(&a)), hash(&TurbRandomState::with_key(&b)));
+        assert_eq!(hash(&TurbRandomState::new()), hash(&TurbRandomState::new()));
+    }
+
+    #[test]
+    fn test_hashmap_usage() {
+        let mut map = HashMap::with_hasher(TurbRandomState::new());
+        map.insert("a", 1);
+        map.insert("b", 2);
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.get("b"), Some(&2));
+    }
+}
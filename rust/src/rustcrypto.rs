@@ -0,0 +1,152 @@
+// =========================================================
+// turb1600 — RustCrypto trait implementations
+//
+// `Turb1600Core` implements the block-level traits from the
+// `digest` crate; `Turb1600` wraps it in `CoreWrapper` so it
+// gets `Digest`, and generic adapters such as `Hmac<Turb1600>`
+// and `Hkdf<Turb1600>` work out of the box.
+// =========================================================
+
+use core::fmt;
+
+use digest::block_buffer::Eager;
+use digest::consts::{U128, U136};
+use digest::core_api::{
+    AlgorithmName, Block, BlockSizeUser, Buffer, BufferKindUser, CoreWrapper,
+    FixedOutputCore, OutputSizeUser, Reset, UpdateCore,
+};
+use digest::{HashMarker, Output};
+
+use crate::core::Sponge;
+
+/// Block-level turb1600 state.
+#[derive(Clone)]
+pub struct Turb1600Core {
+    sponge: Sponge,
+}
+
+/// turb1600 with the full RustCrypto `Digest` API.
+pub type Turb1600 = CoreWrapper<Turb1600Core>;
+
+impl Default for Turb1600Core {
+    fn default() -> Self {
+        Self {
+            sponge: Sponge::new(),
+        }
+    }
+}
+
+impl HashMarker for Turb1600Core {}
+
+impl BlockSizeUser for Turb1600Core {
+    type BlockSize = U136;
+}
+
+impl BufferKindUser for Turb1600Core {
+    // Full blocks are absorbed as soon as they are available,
+    // matching the sponge's own buffering.
+    type BufferKind = Eager;
+}
+
+impl OutputSizeUser for Turb1600Core {
+    type OutputSize = U128;
+}
+
+impl UpdateCore for Turb1600Core {
+    fn update_blocks(&mut self, blocks: &[Block<Self>]) {
+        for block in blocks {
+            self.sponge.update(block);
+        }
+    }
+}
+
+impl FixedOutputCore for Turb1600Core {
+    fn finalize_fixed_core(&mut self, buffer: &mut Buffer<Self>, out: &mut Output<Self>) {
+        let mut sponge = self.sponge.clone();
+        sponge.update(buffer.get_data());
+        sponge.finalize_into(out);
+    }
+}
+
+impl Reset for Turb1600Core {
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl AlgorithmName for Turb1600Core {
+    fn write_alg_name(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Turb1600")
+    }
+}
+
+impl fmt::Debug for Turb1600Core {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Turb1600Core { ... }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use digest::Digest;
+    use hkdf::Hkdf;
+    use hmac::{Hmac, Mac};
+
+    #[test]
+    fn test_digest_matches_one_shot() {
+        let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+        let mut h = Turb1600::new();
+        for chunk in data.chunks(13) {
+            h.update(chunk);
+        }
+        assert_eq!(h.finalize().as_slice(), crate::turb1600_hash(&data).as_slice());
+    }
+
+    #[test]
+    fn test_hmac_generic() {
+        let mut mac = Hmac::<Turb1600>::new_from_slice(b"key").unwrap();
+        mac.update(b"message");
+        let tag = mac.finalize().into_bytes();
+        assert_eq!(
+            hex::encode(tag),
+            "78cd1bfec5bcd49aa39b0c4788d65af2d016a2fbabddb7b71225d43d1b90c16c\
+             b07476ca116c5148d8f3aebf58bd2a7d2da1d0d42a8f2fce014f52b5fd179db8\
+             09e9faebb2a88efe93a0c7b4c8de01615cfab8b22d817c9a53b2729c0033a156\
+             5dc976342d44af0f35bff55aceb63277e1c3507ef82eeb1a1a9fd28e697612d2"
+        );
+
+        // HMAC by hand: H((K ^ opad) || H((K ^ ipad) || m)) over one rate block
+        let block = |pad: u8| {
+            let mut k = [pad; crate::core::BLOCK_BYTES];
+            for (k, b) in k.iter_mut().zip(b"key") {
+                *k ^= b;
+            }
+            k
+        };
+        let inner = crate::turb1600_hash(&[&block(0x36)[..], b"message"].concat());
+        let outer = crate::turb1600_hash(&[&block(0x5c)[..], &inner[..]].concat());
+        assert_eq!(tag.as_slice(), outer.as_slice());
+    }
+
+    #[test]
+    fn test_hkdf_expand_round_trip() {
+        let (prk, hk) = Hkdf::<Turb1600>::extract(Some(b"salt"), b"input key material");
+        let mut okm = [0u8; 300];
+        hk.expand(b"info", &mut okm).unwrap();
+        assert_eq!(
+            hex::encode(&okm[..32]),
+            "d98e8c12f07010f9044db6fb4e65c1956d988b989f8a0e31fc5b8fd70df576fa"
+        );
+
+        let again = Hkdf::<Turb1600>::from_prk(&prk).unwrap();
+        let mut short = [0u8; 42];
+        again.expand(b"info", &mut short).unwrap();
+        assert_eq!(short, okm[..42]);
+
+        let mut other = [0u8; 42];
+        again.expand(b"other info", &mut other).unwrap();
+        assert_ne!(other, short);
+        assert!(hk.expand(b"info", &mut vec![0u8; 255 * 128 + 1]).is_err());
+    }
+}
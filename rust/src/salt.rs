@@ -0,0 +1,57 @@
+// =========================================================
+// turb1600 — Random salts
+//
+// Salts only help if they are unpredictable and never reused,
+// which counters, timestamps and user names are not. These
+// helpers take them from the operating system's RNG, so the
+// salted APIs can pick one themselves when the caller has none:
+//
+//   derive_key_salted(pass, None, ..)      KDF, returns the salt
+//   builder().salt_or_random(None)         salted hasher
+//
+// A generated salt must be stored next to whatever it protected;
+// the same salt is needed to derive the key or hash again.
+// =========================================================
+
+/// Length of the salts generated when none is given (128 bits).
+pub const SALT_BYTES: usize = 16;
+
+/// `N` bytes from the operating system's RNG.
+///
+/// Panics if the RNG is unavailable, which on supported platforms
+/// only happens when the system is badly misconfigured.
+///
+/// ```
+/// let salt: [u8; 16] = turb1600::generate_salt();
+/// assert_ne!(salt, turb1600::generate_salt::<16>());
+/// ```
+pub fn generate_salt<const N: usize>() -> [u8; N] {
+    let mut salt = [0u8; N];
+    getrandom::getrandom(&mut salt).expect("operating system RNG unavailable");
+    salt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kdf::derive_key_salted;
+    use crate::Turb1600;
+
+    #[test]
+    fn test_salted_apis_generate_and_return_salts() {
+        let (key, salt) = derive_key_salted(b"pass", None, 4, 32);
+        assert_eq!(salt.len(), SALT_BYTES);
+        assert_eq!(key, crate::derive_key(b"pass", &salt, 4, 32));
+        let (again, _) = derive_key_salted(b"pass", None, 4, 32);
+        assert_ne!(key, again);
+        assert_eq!(derive_key_salted(b"pass", Some(b"fixed"), 4, 32).1, b"fixed");
+
+        let builder = Turb1600::builder().salt_or_random(None);
+        let salt = builder.salt_bytes().unwrap().to_vec();
+        let mut h = builder.build().unwrap();
+        let mut same = Turb1600::builder().salt(&salt).build().unwrap();
+        h.update(b"data");
+        same.update(b"data");
+        assert_eq!(h.finalize(), same.finalize());
+    }
+}
@@ -0,0 +1,143 @@
+// =========================================================
+// turb1600 — Segment (piece) hashing
+//
+// The stream is cut into fixed-size segments. Each segment gets
+// its plain turb1600 digest, so any segment can be verified on
+// its own, and the segment digests are combined into a root:
+//
+//   segment_i = H(bytes [i*size, (i+1)*size))
+//   root      = H_segment(LE64(size) || LE64(n) || segment_0 || … || segment_{n-1})
+//
+// H_segment is the sponge started from its own domain tag, so a
+// root is never also the plain digest of a 16 + 128·n byte file.
+// The last segment may be short. An empty stream has no segments
+// (n = 0). Unlike tree mode, segments carry no index or prefix.
+// =========================================================
+
+use crate::core::{Sponge, Turb1600, SEGMENT_TAG};
+use crate::error::Turb1600Error;
+
+/// Streaming hasher producing one digest per fixed-size segment.
+#[derive(Clone)]
+pub struct SegmentHasher {
+    segment_size: u64,
+    current: Turb1600,
+    filled: u64,
+    segments: Vec<Vec<u8>>,
+}
+
+/// Result of [`SegmentHasher::finalize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentDigests {
+    /// Digest of each segment, in stream order.
+    pub segments: Vec<Vec<u8>>,
+    /// Root over `segments`.
+    pub root: Vec<u8>,
+}
+
+impl SegmentHasher {
+    /// Panics if `segment_size` is zero; see [`try_new`](Self::try_new).
+    pub fn new(segment_size: u64) -> Self {
+        Self::try_new(segment_size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// [`new`](Self::new), failing with
+    /// [`Turb1600Error::ZeroSegmentSize`] instead of panicking.
+    pub fn try_new(segment_size: u64) -> Result<Self, Turb1600Error> {
+        if segment_size == 0 {
+            return Err(Turb1600Error::ZeroSegmentSize);
+        }
+        Ok(Self {
+            segment_size,
+            current: Turb1600::new(),
+            filled: 0,
+            segments: Vec::new(),
+        })
+    }
+
+    pub fn segment_size(&self) -> u64 {
+        self.segment_size
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let room = self.segment_size - self.filled;
+            let take = room.min(data.len() as u64) as usize;
+            self.current.update(&data[..take]);
+            self.filled += take as u64;
+            data = &data[take..];
+
+            if self.filled == self.segment_size {
+                self.finish_segment();
+            }
+        }
+    }
+
+    /// Digests of the segments completed so far.
+    ///
+    /// A segment is complete once `segment_size` bytes of it have
+    /// been seen; the trailing partial one appears after `finalize`.
+    pub fn segments(&self) -> &[Vec<u8>] {
+        &self.segments
+    }
+
+    pub fn finalize(mut self) -> SegmentDigests {
+        if self.filled > 0 {
+            self.finish_segment();
+        }
+
+        let mut root = Sponge::with_tag(SEGMENT_TAG);
+        root.update(&self.segment_size.to_le_bytes());
+        root.update(&(self.segments.len() as u64).to_le_bytes());
+        for digest in &self.segments {
+            root.update(digest);
+        }
+
+        SegmentDigests {
+            segments: self.segments,
+            root: root.finalize(),
+        }
+    }
+
+    fn finish_segment(&mut self) {
+        let digest = std::mem::take(&mut self.current).finalize();
+        self.segments.push(digest);
+        self.filled = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_are_plain_digests() {
+        let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+
+        let mut hasher = SegmentHasher::new(300);
+        for part in data.chunks(77) {
+            hasher.update(part);
+        }
+        assert_eq!(hasher.segments().len(), 3);
+        let out = hasher.finalize();
+
+        let expected: Vec<Vec<u8>> = data.chunks(300).map(crate::turb1600_hash).collect();
+        assert_eq!(out.segments, expected);
+
+        let mut root = Vec::new();
+        root.extend_from_slice(&300u64.to_le_bytes());
+        root.extend_from_slice(&4u64.to_le_bytes());
+        expected.iter().for_each(|d| root.extend_from_slice(d));
+        let mut sponge = Sponge::with_tag(SEGMENT_TAG);
+        sponge.update(&root);
+        assert_eq!(out.root, sponge.finalize());
+        assert_ne!(out.root, crate::turb1600_hash(&root));
+    }
+
+    #[test]
+    fn test_empty_stream_has_no_segments() {
+        let out = SegmentHasher::new(64).finalize();
+        assert!(out.segments.is_empty());
+        assert_ne!(out.root, SegmentHasher::new(128).finalize().root);
+    }
+}
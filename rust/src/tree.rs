@@ -0,0 +1,342 @@
+// =========================================================
+// turb1600 — Tree hashing mode
+//
+// The input is split into fixed-size chunks. Each chunk is
+// hashed independently as a leaf, then the leaf digests are
+// hashed together into a single root:
+//
+//   leaf_i = H_tree(0x00 || LE64(i) || chunk_i)
+//   root   = H_tree(0x01 || LE64(chunk_size) || LE64(n) || leaf_0 || … || leaf_{n-1})
+//
+// H_tree is the sponge started from its own domain tag, so no
+// root or leaf is ever the plain digest of some flat message.
+// An empty input is a single empty leaf. The root depends on
+// the chunk size, so producers and verifiers must agree on it.
+// =========================================================
+
+use std::io::{self, Read};
+
+use crate::core::{Sponge, DIGEST_BYTES, TREE_TAG};
+use crate::error::Turb1600Error;
+
+/// Default leaf size for tree hashing (1 MiB).
+pub const TREE_CHUNK_DEFAULT: usize = 1 << 20;
+
+const LEAF_PREFIX: u8 = 0x00;
+const ROOT_PREFIX: u8 = 0x01;
+
+/// `n / d` rounded up, without the overflow of `(n + d - 1) / d`
+#[inline]
+fn ceil_div(n: usize, d: usize) -> usize {
+    n / d + usize::from(n % d != 0)
+}
+
+#[inline]
+fn leaf_count(len: usize, chunk_size: usize) -> usize {
+    assert!(chunk_size > 0, "tree chunk size must be non-zero");
+    ceil_div(len, chunk_size).max(1)
+}
+
+#[inline]
+fn hash_leaf(index: usize, chunk: &[u8], out: &mut [u8]) {
+    let mut sponge = Sponge::with_tag(TREE_TAG);
+    sponge.update(&[LEAF_PREFIX]);
+    sponge.update(&(index as u64).to_le_bytes());
+    sponge.update(chunk);
+    sponge.finalize_into(out);
+}
+
+fn hash_root(chunk_size: usize, leaves: &[u8]) -> Vec<u8> {
+    let mut sponge = Sponge::with_tag(TREE_TAG);
+    sponge.update(&[ROOT_PREFIX]);
+    sponge.update(&(chunk_size as u64).to_le_bytes());
+    sponge.update(&((leaves.len() / DIGEST_BYTES) as u64).to_le_bytes());
+    sponge.update(leaves);
+    sponge.finalize()
+}
+
+/// Hash `data` in tree mode on the current thread.
+///
+/// Panics if `chunk_size` is zero; see [`try_turb1600_tree_hash`].
+pub fn turb1600_tree_hash(data: &[u8], chunk_size: usize) -> Vec<u8> {
+    let n = leaf_count(data.len(), chunk_size);
+    let mut leaves = vec![0u8; n * DIGEST_BYTES];
+
+    if data.is_empty() {
+        hash_leaf(0, data, &mut leaves);
+    } else {
+        for (i, (chunk, out)) in data
+            .chunks(chunk_size)
+            .zip(leaves.chunks_exact_mut(DIGEST_BYTES))
+            .enumerate()
+        {
+            hash_leaf(i, chunk, out);
+        }
+    }
+
+    hash_root(chunk_size, &leaves)
+}
+
+/// [`turb1600_tree_hash`], failing with
+/// [`Turb1600Error::ZeroChunkSize`] instead of panicking.
+pub fn try_turb1600_tree_hash(data: &[u8], chunk_size: usize) -> Result<Vec<u8>, Turb1600Error> {
+    if chunk_size == 0 {
+        return Err(Turb1600Error::ZeroChunkSize);
+    }
+    Ok(turb1600_tree_hash(data, chunk_size))
+}
+
+/// Readers report a zero chunk size as `InvalidInput`
+fn check_chunk_size(chunk_size: usize) -> io::Result<()> {
+    if chunk_size == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, Turb1600Error::ZeroChunkSize));
+    }
+    Ok(())
+}
+
+/// Fill `buf` from `reader`, stopping early only at EOF.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Hash everything `reader` yields in tree mode, holding one chunk
+/// in memory at a time.
+///
+/// Produces the same digest as [`turb1600_tree_hash`] over the same
+/// bytes. A zero `chunk_size` is an `InvalidInput` error wrapping
+/// [`Turb1600Error::ZeroChunkSize`], and a chunk that cannot be
+/// allocated is an `OutOfMemory` error.
+pub fn turb1600_tree_hash_reader<R: Read>(mut reader: R, chunk_size: usize) -> io::Result<Vec<u8>> {
+    check_chunk_size(chunk_size)?;
+
+    let mut chunk = Vec::new();
+    chunk
+        .try_reserve_exact(chunk_size)
+        .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "tree chunk is too large to allocate"))?;
+    chunk.resize(chunk_size, 0);
+    let mut leaves = Vec::new();
+    loop {
+        let filled = read_full(&mut reader, &mut chunk)?;
+        // A short chunk is the last one; an empty input still gets one leaf
+        if filled > 0 || leaves.is_empty() {
+            let index = leaves.len() / DIGEST_BYTES;
+            leaves.resize(leaves.len() + DIGEST_BYTES, 0);
+            hash_leaf(index, &chunk[..filled], &mut leaves[index * DIGEST_BYTES..]);
+        }
+        if filled < chunk_size {
+            return Ok(hash_root(chunk_size, &leaves));
+        }
+    }
+}
+
+/// Hash `data` in tree mode, spreading leaves over scoped threads.
+///
+/// Uses up to `threads` workers (`0` means one per available core).
+/// Produces the same digest as [`turb1600_tree_hash`].
+///
+/// Panics if `chunk_size` is zero.
+#[cfg(feature = "threads")]
+pub fn turb1600_tree_hash_parallel(data: &[u8], chunk_size: usize, threads: usize) -> Vec<u8> {
+    let n = leaf_count(data.len(), chunk_size);
+    let threads = thread_count(threads).min(n);
+
+    if threads <= 1 {
+        return turb1600_tree_hash(data, chunk_size);
+    }
+
+    let mut leaves = vec![0u8; n * DIGEST_BYTES];
+    hash_leaves_parallel(data, chunk_size, 0, &mut leaves, threads);
+    hash_root(chunk_size, &leaves)
+}
+
+/// Like [`turb1600_tree_hash_reader`], for inputs too large to hold
+/// in memory: a reader thread fills batches of `threads` chunks
+/// while the previous batch's leaves are hashed on scoped threads.
+///
+/// `threads` as in [`turb1600_tree_hash_parallel`]. Produces the
+/// same digest as [`turb1600_tree_hash`]. Two batches of
+/// `chunk_size × threads` bytes are allocated up front; a batch that
+/// overflows or cannot be allocated is an `OutOfMemory` error, and
+/// a zero `chunk_size` is `InvalidInput`.
+#[cfg(feature = "threads")]
+pub fn turb1600_tree_hash_reader_parallel<R: Read + Send>(
+    mut reader: R,
+    chunk_size: usize,
+    threads: usize,
+) -> io::Result<Vec<u8>> {
+    use std::sync::mpsc;
+
+    check_chunk_size(chunk_size)?;
+    let threads = thread_count(threads);
+    let too_large = || io::Error::new(io::ErrorKind::OutOfMemory, "tree chunk batch is too large to allocate");
+    let batch = chunk_size.checked_mul(threads).ok_or_else(too_large)?;
+
+    let (full_tx, full_rx) = mpsc::sync_channel::<io::Result<(Vec<u8>, usize)>>(1);
+    let (empty_tx, empty_rx) = mpsc::sync_channel::<Vec<u8>>(2);
+    for _ in 0..2 {
+        let mut buf = Vec::new();
+        buf.try_reserve_exact(batch).map_err(|_| too_large())?;
+        buf.resize(batch, 0);
+        empty_tx.send(buf).expect("buffer pool");
+    }
+
+    // Both channel ends move into the scope, so an early error return
+    // drops them and lets the reader thread exit before the join.
+    std::thread::scope(move |scope| {
+        scope.spawn(move || {
+            while let Ok(mut buf) = empty_rx.recv() {
+                let result = read_full(&mut reader, &mut buf);
+                let more = matches!(result, Ok(n) if n == buf.len());
+                if full_tx.send(result.map(|n| (buf, n))).is_err() || !more {
+                    break;
+                }
+            }
+        });
+
+        let mut leaves = Vec::new();
+        for batch in full_rx {
+            let (buf, filled) = batch?;
+            let first = leaves.len() / DIGEST_BYTES;
+            leaves.resize(leaves.len() + ceil_div(filled, chunk_size) * DIGEST_BYTES, 0);
+            hash_leaves_parallel(&buf[..filled], chunk_size, first, &mut leaves[first * DIGEST_BYTES..], threads);
+            // The reader may already have finished; a closed pool is fine.
+            let _ = empty_tx.send(buf);
+        }
+
+        if leaves.is_empty() {
+            leaves.resize(DIGEST_BYTES, 0);
+            hash_leaf(0, &[], &mut leaves);
+        }
+        Ok(hash_root(chunk_size, &leaves))
+    })
+}
+
+/// Resolve a `threads` argument: `0` means one per available core.
+#[cfg(feature = "threads")]
+fn thread_count(threads: usize) -> usize {
+    match threads {
+        0 => std::thread::available_parallelism().map_or(1, |t| t.get()),
+        t => t,
+    }
+}
+
+/// Hash the chunks of `data` as leaves numbered from `first` into
+/// `out`, spread over up to `threads` scoped threads.
+#[cfg(feature = "threads")]
+fn hash_leaves_parallel(data: &[u8], chunk_size: usize, first: usize, out: &mut [u8], threads: usize) {
+    let n = out.len() / DIGEST_BYTES;
+    let threads = threads.max(1);
+    let per_thread = ceil_div(n, threads).max(1);
+
+    std::thread::scope(|scope| {
+        for (t, out) in out.chunks_mut(per_thread * DIGEST_BYTES).enumerate() {
+            let start_leaf = t * per_thread;
+            let start = start_leaf * chunk_size;
+            let end = (start + per_thread * chunk_size).min(data.len());
+            let data = &data[start..end];
+
+            scope.spawn(move || {
+                for (i, (chunk, out)) in data
+                    .chunks(chunk_size)
+                    .zip(out.chunks_exact_mut(DIGEST_BYTES))
+                    .enumerate()
+                {
+                    hash_leaf(first + start_leaf + i, chunk, out);
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_reader_matches_slice() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+        for len in [0, 1, 99, 100, 101, 1000] {
+            assert_eq!(
+                turb1600_tree_hash_reader(&data[..len], 100).unwrap(),
+                turb1600_tree_hash(&data[..len], 100),
+                "len {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_tree_depends_on_chunk_size() {
+        let data = vec![0x5au8; 1000];
+        assert_ne!(turb1600_tree_hash(&data, 64), turb1600_tree_hash(&data, 128));
+        assert_ne!(turb1600_tree_hash(&data, 64), crate::turb1600_hash(&data));
+    }
+
+    #[test]
+    fn test_tree_is_domain_separated() {
+        // The root's own preimage, hashed flat, must give something else
+        let mut leaf = vec![0u8; DIGEST_BYTES];
+        hash_leaf(0, b"abc", &mut leaf);
+        let preimage = [&[ROOT_PREFIX][..], &64u64.to_le_bytes(), &1u64.to_le_bytes(), &leaf].concat();
+        assert_ne!(turb1600_tree_hash(b"abc", 64), crate::turb1600_hash(&preimage));
+        assert_eq!(turb1600_tree_hash(b"abc", 64), hash_root(64, &leaf));
+    }
+
+    #[test]
+    fn test_tree_reader_rejects_unallocatable_chunk() {
+        let err = turb1600_tree_hash_reader(&b"abc"[..], usize::MAX / 2).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::OutOfMemory);
+    }
+
+    #[test]
+    fn test_huge_chunk_size_is_one_leaf() {
+        let data = vec![0x5au8; 1000];
+        let one_leaf = turb1600_tree_hash(&data, usize::MAX);
+        assert_eq!(try_turb1600_tree_hash(&data, usize::MAX).unwrap(), one_leaf);
+        assert_eq!(leaf_count(data.len(), usize::MAX), 1);
+        assert_eq!(leaf_count(usize::MAX, usize::MAX - 1), 2);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn test_tree_parallel_matches_sequential() {
+        let data: Vec<u8> = (0..3_000u32).map(|i| i as u8).collect();
+        for chunk in [1, 100, 999, 4096] {
+            let seq = turb1600_tree_hash(&data, chunk);
+            for threads in [0, 2, 3, 8] {
+                assert_eq!(turb1600_tree_hash_parallel(&data, chunk, threads), seq);
+            }
+        }
+        for chunk in [1, 100, 999, 4096] {
+            for threads in [1, 3] {
+                assert_eq!(
+                    turb1600_tree_hash_reader_parallel(&data[..], chunk, threads).unwrap(),
+                    turb1600_tree_hash(&data, chunk)
+                );
+            }
+        }
+        assert_eq!(
+            turb1600_tree_hash_reader_parallel(&[][..], 64, 4).unwrap(),
+            turb1600_tree_hash(&[], 64)
+        );
+        assert_eq!(
+            turb1600_tree_hash_parallel(&[], 64, 4),
+            turb1600_tree_hash(&[], 64)
+        );
+
+        // An impossible batch is an error, not an abort
+        for (chunk, threads) in [(usize::MAX / 2, 4), (usize::MAX / 4, 2)] {
+            let err = turb1600_tree_hash_reader_parallel(&data[..], chunk, threads).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::OutOfMemory);
+        }
+    }
+}
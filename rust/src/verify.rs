@@ -0,0 +1,169 @@
+// =========================================================
+// turb1600 — Verify-while-reading
+//
+// `VerifyingReader` passes data through from an inner reader,
+// hashing it on the way, and turns a digest mismatch into an
+// `InvalidData` error (wrapping `Turb1600Error::DigestMismatch`)
+// when the stream ends. Consumers must treat the bytes as
+// untrusted until a read returns `Ok(0)`. After a mismatch every
+// further read returns the same error.
+//
+// With a declared length the stream ends after exactly that many
+// bytes: the read that completes it is checked first and fails
+// instead of returning the final bytes if the digest is wrong,
+// and an inner reader that ends early is `UnexpectedEof`.
+// =========================================================
+
+use std::io::{self, Read};
+
+use crate::core::Turb1600;
+use crate::error::Turb1600Error;
+use crate::output::Digest;
+
+/// Reader adapter that enforces an expected digest.
+///
+/// ```
+/// use std::io::Read;
+/// use turb1600::{Digest, VerifyingReader};
+///
+/// let data = b"release payload";
+/// let mut reader = VerifyingReader::new(&data[..], Digest::of(data));
+/// let mut out = Vec::new();
+/// reader.read_to_end(&mut out)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct VerifyingReader<R> {
+    inner: R,
+    expected: Digest,
+    state: State,
+    /// Bytes still to read, when the length is declared
+    remaining: Option<u64>,
+}
+
+enum State {
+    Reading(Box<Turb1600>),
+    Verified,
+    /// Every later read repeats the mismatch error
+    Failed,
+}
+
+impl<R: Read> VerifyingReader<R> {
+    /// Verify everything `inner` yields up to its EOF.
+    pub fn new(inner: R, expected: Digest) -> Self {
+        Self {
+            inner,
+            expected,
+            state: State::Reading(Box::default()),
+            remaining: None,
+        }
+    }
+
+    /// Verify exactly `len` bytes of `inner`; anything after them is
+    /// left unread.
+    pub fn with_length(inner: R, expected: Digest, len: u64) -> Self {
+        Self {
+            remaining: Some(len),
+            ..Self::new(inner, expected)
+        }
+    }
+
+    /// Whether the stream has ended and its digest matched.
+    pub fn is_verified(&self) -> bool {
+        matches!(self.state, State::Verified)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let State::Reading(hasher) = std::mem::replace(&mut self.state, State::Failed) else {
+            unreachable!("checked once");
+        };
+        if hasher.finalize_digest() != self.expected {
+            return Err(Turb1600Error::DigestMismatch.into());
+        }
+        self.state = State::Verified;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for VerifyingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let hasher = match &mut self.state {
+            State::Reading(hasher) => hasher,
+            State::Failed => return Err(Turb1600Error::DigestMismatch.into()),
+            State::Verified => {
+                return match self.remaining {
+                    Some(_) => Ok(0),
+                    None => self.inner.read(buf),
+                };
+            }
+        };
+
+        let want = match self.remaining {
+            Some(left) => buf.len().min(usize::try_from(left).unwrap_or(usize::MAX)),
+            None => buf.len(),
+        };
+        if want == 0 && self.remaining == Some(0) {
+            self.finish()?;
+            return Ok(0);
+        }
+
+        let n = self.inner.read(&mut buf[..want])?;
+        hasher.update(&buf[..n]);
+
+        match &mut self.remaining {
+            Some(_) if n == 0 && want > 0 => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "stream ended before the declared length",
+            )),
+            Some(left) => {
+                *left -= n as u64;
+                if *left == 0 {
+                    self.finish()?;
+                }
+                Ok(n)
+            }
+            None if n == 0 && want > 0 => self.finish().map(|()| 0),
+            None => Ok(n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all<R: Read>(mut r: R) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        r.read_to_end(&mut out).map(|_| out)
+    }
+
+    #[test]
+    fn test_verifying_reader() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 5) as u8).collect();
+        let good = Digest::of(&data);
+        let bad = Digest::of(b"something else");
+
+        assert_eq!(read_all(VerifyingReader::new(&data[..], good)).unwrap(), data);
+        let err = read_all(VerifyingReader::new(&data[..], bad)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // A mismatch sticks: reading again must not look like a clean EOF
+        let mut reader = VerifyingReader::with_length(&data[..], bad, 4000);
+        assert!(read_all(&mut reader).is_err());
+        let mut buf = [0u8; 16];
+        assert_eq!(reader.read(&mut buf).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(!reader.is_verified());
+
+        // Declared length: trailing bytes stay unread, short input is an error
+        let mut reader = VerifyingReader::with_length(&data[..], Digest::of(&data[..4000]), 4000);
+        assert_eq!(read_all(&mut reader).unwrap(), &data[..4000]);
+        assert!(reader.is_verified());
+        assert_eq!(reader.into_inner().len(), 6000);
+
+        let err = read_all(VerifyingReader::with_length(&data[..], good, 20_000)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,64 @@
+// =========================================================
+// turb1600 — Word fingerprints
+//
+// Renders bytes as words from the BIP-39 English list for
+// reading aloud, e.g. when confirming a device pairing. Bytes
+// are taken as a big-endian bit string and cut into 11-bit word
+// indices; a trailing partial group is padded with zero bits.
+// Unlike a BIP-39 mnemonic there is no checksum word, so the
+// output is a fingerprint, not a recoverable encoding of keys.
+// =========================================================
+
+mod english;
+
+use english::WORDS;
+
+/// Bytes of the digest shown by default (64 bits, six words).
+pub const WORDS_DEFAULT_BYTES: usize = 8;
+
+const BITS_PER_WORD: u32 = 11;
+
+/// Encode `bytes` as `ceil(8 * len / 11)` words.
+pub fn encode_words(bytes: &[u8]) -> Vec<&'static str> {
+    let (len_bits, per_word) = (bytes.len() * 8, BITS_PER_WORD as usize);
+    let mut words = Vec::with_capacity(len_bits / per_word + usize::from(len_bits % per_word != 0));
+    let (mut acc, mut bits) = (0u32, 0u32);
+
+    for &b in bytes {
+        acc = (acc << 8) | u32::from(b);
+        bits += 8;
+        if bits >= BITS_PER_WORD {
+            bits -= BITS_PER_WORD;
+            words.push(WORDS[((acc >> bits) & 0x7ff) as usize]);
+        }
+    }
+    if bits > 0 {
+        words.push(WORDS[((acc << (BITS_PER_WORD - bits)) & 0x7ff) as usize]);
+    }
+
+    words
+}
+
+/// Space-separated words for the first `n` bytes of `digest`.
+///
+/// Panics if `n` exceeds `digest.len()`.
+pub fn fingerprint_words(digest: &[u8], n: usize) -> String {
+    encode_words(&digest[..n]).join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_boundaries() {
+        assert_eq!(encode_words(&[]), Vec::<&str>::new());
+        // 0000_0000 001|0_0000 (padded) -> indices 1, 0
+        assert_eq!(encode_words(&[0x00, 0x20]), ["ability", "abandon"]);
+        // 88 one bits -> eight times index 2047
+        assert_eq!(encode_words(&[0xff; 11]), ["zoo"; 8]);
+
+        let digest = crate::turb1600_hash(b"pairing");
+        assert_eq!(fingerprint_words(&digest, WORDS_DEFAULT_BYTES).split(' ').count(), 6);
+    }
+}